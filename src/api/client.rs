@@ -43,7 +43,10 @@ pub(crate) fn create_client(token: String) -> Client {
 /// while still allowing for overrding for tests or local mock server
 #[async_trait(?Send)]
 pub trait SlackApiClient {
-    async fn schedule_message(&self, request: &ScheduleMessageRequest) -> ScheduleMessageResponse;
+    async fn schedule_message(
+        &self,
+        request: &ScheduleMessageRequest,
+    ) -> SlackApiResponse<ScheduleMessageResponse>;
 
     async fn join_conversation(
         &self,
@@ -53,6 +56,16 @@ pub trait SlackApiClient {
         &self,
         request: &UserLookupRequest,
     ) -> SlackApiResponse<UserLookupResponse>;
+    async fn user_info(&self, request: &UserInfoRequest) -> SlackApiResponse<UserLookupResponse>;
+    async fn list_users(&self, request: &UsersListRequest) -> SlackApiResponse<UsersListResponse>;
+    async fn list_usergroups(
+        &self,
+        request: &UsergroupsListRequest,
+    ) -> SlackApiResponse<UsergroupsListResponse>;
+    async fn list_usergroup_users(
+        &self,
+        request: &UsergroupUsersListRequest,
+    ) -> SlackApiResponse<UsergroupUsersListResponse>;
 
     async fn list_members(
         &self,
@@ -67,6 +80,11 @@ pub trait SlackApiClient {
         request: &DeleteScheduledMessageRequest,
     ) -> SlackApiResponse<Empty>;
     async fn auth_test(&self) -> SlackApiResponse<BotIdentity>;
+
+    /// Whether writes are only simulated, in which case local files must not be written either.
+    fn is_dry_run(&self) -> bool {
+        false
+    }
 }
 
 pub(crate) struct ProdSlackApiClient {
@@ -82,10 +100,15 @@ impl ProdSlackApiClient {
 
 #[async_trait(?Send)]
 impl SlackApiClient for ProdSlackApiClient {
-    async fn schedule_message(&self, request: &ScheduleMessageRequest) -> ScheduleMessageResponse {
+    async fn schedule_message(
+        &self,
+        request: &ScheduleMessageRequest,
+    ) -> SlackApiResponse<ScheduleMessageResponse> {
         let endpoint = ScheduleMessageEndpoint;
-        let response_raw = endpoint.call_endpoint(request, &self.client).await;
-        ScheduleMessageResponse::from(response_raw.unwrap())
+        endpoint
+            .call_endpoint(request, &self.client)
+            .await
+            .map(ScheduleMessageResponse::from)
     }
 
     async fn join_conversation(
@@ -104,6 +127,32 @@ impl SlackApiClient for ProdSlackApiClient {
         endpoint.call_endpoint(request, &self.client).await
     }
 
+    async fn user_info(&self, request: &UserInfoRequest) -> SlackApiResponse<UserLookupResponse> {
+        let endpoint = UserInfoEndpoint;
+        endpoint.call_endpoint(request, &self.client).await
+    }
+
+    async fn list_users(&self, request: &UsersListRequest) -> SlackApiResponse<UsersListResponse> {
+        let endpoint = UsersListEndpoint;
+        endpoint.call_endpoint(request, &self.client).await
+    }
+
+    async fn list_usergroups(
+        &self,
+        request: &UsergroupsListRequest,
+    ) -> SlackApiResponse<UsergroupsListResponse> {
+        let endpoint = UsergroupsListEndpoint;
+        endpoint.call_endpoint(request, &self.client).await
+    }
+
+    async fn list_usergroup_users(
+        &self,
+        request: &UsergroupUsersListRequest,
+    ) -> SlackApiResponse<UsergroupUsersListResponse> {
+        let endpoint = UsergroupUsersListEndpoint;
+        endpoint.call_endpoint(request, &self.client).await
+    }
+
     async fn list_members(
         &self,
         request: &ListMembersRequestParams,
@@ -138,25 +187,49 @@ impl SlackApiClient for ProdSlackApiClient {
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use serde_json::from_str;
+    use serde_json::{from_str, json, Value};
+    use std::cell::{Cell, RefCell};
 
     pub struct TestSlackClient {
         schedule_message_res: String,
         join_conversation_res: String,
         user_lookup_res: String,
+        list_users_res: String,
+        list_usergroups_res: String,
+        list_usergroup_users_res: String,
         list_members_res: String,
         list_scheduled_messages_res: String,
         delete_scheduled_message_res: String,
         auth_test_res: String,
+        /// Messages scheduled through the client and not deleted yet, listed after the canned one.
+        scheduled: RefCell<Vec<Value>>,
+        /// IDs of the scheduled messages deleted through the client.
+        deleted: RefCell<Vec<String>>,
+        /// Number of messages scheduled before Slack starts refusing them, when limited.
+        schedule_limit: Option<usize>,
+        schedule_count: Cell<usize>,
     }
 
     #[async_trait(?Send)]
     impl SlackApiClient for TestSlackClient {
         async fn schedule_message(
             &self,
-            _request: &ScheduleMessageRequest,
-        ) -> ScheduleMessageResponse {
-            from_str(&self.schedule_message_res).unwrap()
+            request: &ScheduleMessageRequest,
+        ) -> SlackApiResponse<ScheduleMessageResponse> {
+            let count = self.schedule_count.get() + 1;
+            self.schedule_count.set(count);
+            if self.schedule_limit.is_some_and(|limit| count > limit) {
+                return from_str(r#"{"ok": false, "error": "no_permission"}"#).unwrap();
+            }
+            // Distinct IDs and the requested time, as Slack answers.
+            let id = format!("Q{}", 1298393283 + count);
+            let mut response: Value = from_str(&self.schedule_message_res).unwrap();
+            response["scheduled_message_id"] = json!(id);
+            response["post_at"] = json!(request.post_at);
+            self.schedule(&id, request.post_at, &request.text);
+            serde_json::from_value::<SlackApiResponse<ScheduleMessageResponseRaw>>(response)
+                .unwrap()
+                .map(ScheduleMessageResponse::from)
         }
 
         async fn join_conversation(
@@ -173,6 +246,34 @@ pub mod tests {
             from_str(&self.user_lookup_res).unwrap()
         }
 
+        async fn user_info(
+            &self,
+            _request: &UserInfoRequest,
+        ) -> SlackApiResponse<UserLookupResponse> {
+            from_str(&self.user_lookup_res).unwrap()
+        }
+
+        async fn list_users(
+            &self,
+            _request: &UsersListRequest,
+        ) -> SlackApiResponse<UsersListResponse> {
+            from_str(&self.list_users_res).unwrap()
+        }
+
+        async fn list_usergroups(
+            &self,
+            _request: &UsergroupsListRequest,
+        ) -> SlackApiResponse<UsergroupsListResponse> {
+            from_str(&self.list_usergroups_res).unwrap()
+        }
+
+        async fn list_usergroup_users(
+            &self,
+            _request: &UsergroupUsersListRequest,
+        ) -> SlackApiResponse<UsergroupUsersListResponse> {
+            from_str(&self.list_usergroup_users_res).unwrap()
+        }
+
         async fn list_members(
             &self,
             _request: &ListMembersRequestParams,
@@ -184,13 +285,25 @@ pub mod tests {
             &self,
             _request: &ScheduledMessagesListRequest,
         ) -> SlackApiResponse<ScheduledMessagesListRaw> {
-            from_str(&self.list_scheduled_messages_res).unwrap()
+            let mut response: Value = from_str(&self.list_scheduled_messages_res).unwrap();
+            response["scheduled_messages"]
+                .as_array_mut()
+                .unwrap()
+                .extend(self.scheduled.borrow().iter().cloned());
+            serde_json::from_value(response).unwrap()
         }
 
         async fn delete_scheduled_message(
             &self,
-            _request: &DeleteScheduledMessageRequest,
+            request: &DeleteScheduledMessageRequest,
         ) -> SlackApiResponse<Empty> {
+            let id = serde_json::to_value(request).unwrap()["scheduled_message_id"].clone();
+            self.scheduled
+                .borrow_mut()
+                .retain(|message| message["id"] != id);
+            self.deleted
+                .borrow_mut()
+                .push(id.as_str().unwrap().to_string());
             from_str(&self.delete_scheduled_message_res).unwrap()
         }
 
@@ -199,6 +312,37 @@ pub mod tests {
         }
     }
 
+    impl TestSlackClient {
+        /// Client for which Slack refuses to schedule any message.
+        pub fn failing_schedules() -> Self {
+            TestSlackClient::failing_schedules_after(0)
+        }
+
+        /// Client for which Slack schedules `count` messages, then refuses the next ones.
+        pub fn failing_schedules_after(count: usize) -> Self {
+            TestSlackClient {
+                schedule_limit: Some(count),
+                ..TestSlackClient::default()
+            }
+        }
+
+        /// Adds a message to those Slack lists as scheduled in the channel.
+        pub fn schedule(&self, id: &str, post_at: i64, text: &str) {
+            self.scheduled.borrow_mut().push(json!({
+                "id": id,
+                "channel_id": "C1H9RESGL",
+                "post_at": post_at,
+                "date_created": 1551891734,
+                "text": text,
+            }));
+        }
+
+        /// IDs of the scheduled messages deleted so far, in order.
+        pub fn deleted(&self) -> Vec<String> {
+            self.deleted.borrow().clone()
+        }
+    }
+
     impl Default for TestSlackClient {
         fn default() -> Self {
             let schedule_message_res = r#"{
@@ -315,6 +459,70 @@ pub mod tests {
                 }
             }"#
             .to_string();
+            let list_users_res = r#"{
+                "ok": true,
+                "members": [
+                    {
+                        "id": "W012A3CDE",
+                        "name": "spengler",
+                        "real_name": "Egon Spengler",
+                        "profile": {"display_name": "spengler", "real_name": "Egon Spengler"}
+                    },
+                    {
+                        "id": "U023BECGF",
+                        "name": "stantz",
+                        "real_name": "Ray Stantz",
+                        "profile": {"display_name": "Ray", "real_name": "Ray Stantz"}
+                    },
+                    {
+                        "id": "U061F7AUR",
+                        "name": "parker",
+                        "real_name": "Ray Parker",
+                        "profile": {"display_name": "Ray", "real_name": "Ray Parker"}
+                    },
+                    {
+                        "id": "B0BOT0001",
+                        "name": "ecto1",
+                        "is_bot": true,
+                        "profile": {"display_name": ""}
+                    }
+                ],
+                "response_metadata": {
+                    "next_cursor": ""
+                }
+            }"#
+            .to_string();
+            let list_usergroups_res = r#"{
+                "ok": true,
+                "usergroups": [
+                    {
+                        "id": "S0614TZR7",
+                        "team_id": "T060RNRCH",
+                        "is_usergroup": true,
+                        "name": "Jokes Committee",
+                        "description": "Keeps the jokes coming",
+                        "handle": "jokes-committee",
+                        "is_external": false,
+                        "date_create": 1446746793,
+                        "date_update": 1446746793,
+                        "date_delete": 0,
+                        "auto_type": null,
+                        "created_by": "U060RNRCZ",
+                        "updated_by": "U060RNRCZ",
+                        "deleted_by": null,
+                        "user_count": 2
+                    }
+                ]
+            }"#
+            .to_string();
+            let list_usergroup_users_res = r#"{
+                "ok": true,
+                "users": [
+                    "W012A3CDE",
+                    "U061F7AUR"
+                ]
+            }"#
+            .to_string();
             let list_members_res = r#"{
                 "ok": true,
                 "members": [
@@ -363,10 +571,17 @@ pub mod tests {
                 schedule_message_res,
                 join_conversation_res,
                 user_lookup_res,
+                list_users_res,
+                list_usergroups_res,
+                list_usergroup_users_res,
                 list_members_res,
                 list_scheduled_messages_res,
                 delete_scheduled_message_res,
                 auth_test_res,
+                scheduled: RefCell::new(Vec::new()),
+                deleted: RefCell::new(Vec::new()),
+                schedule_limit: None,
+                schedule_count: Cell::new(0),
             }
         }
     }
@@ -0,0 +1,181 @@
+use super::*;
+use async_trait::async_trait;
+use serde_json::json;
+use std::cell::Cell;
+
+/// Wraps a client to simulate writes: reads still go to Slack,
+/// but scheduling, deleting and joining are printed on stderr instead of sent, and answered with a fake success.
+pub(crate) struct DryRunSlackApiClient<Api: SlackApiClient> {
+    api: Api,
+    /// Numbers the simulated scheduled messages, so they get distinct IDs.
+    scheduled_count: Cell<usize>,
+}
+
+impl<Api: SlackApiClient> DryRunSlackApiClient<Api> {
+    pub fn new(api: Api) -> DryRunSlackApiClient<Api> {
+        DryRunSlackApiClient {
+            api,
+            scheduled_count: Cell::new(0),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<Api: SlackApiClient> SlackApiClient for DryRunSlackApiClient<Api> {
+    async fn schedule_message(
+        &self,
+        request: &ScheduleMessageRequest,
+    ) -> SlackApiResponse<ScheduleMessageResponse> {
+        let post_at = Local.timestamp(request.post_at, 0);
+        eprintln!(
+            "[dry run] Would schedule in {} at {}: {}",
+            request.channel, post_at, request.text
+        );
+        let count = self.scheduled_count.get() + 1;
+        self.scheduled_count.set(count);
+        let raw: SlackApiResponse<ScheduleMessageResponseRaw> = serde_json::from_value(json!({
+            "ok": true,
+            "channel": request.channel,
+            "scheduled_message_id": format!("dry-run-{}", count),
+            "post_at": request.post_at,
+            "message": {
+                "text": request.text,
+                "username": "dry-run",
+                "type": "delayed_message",
+            }
+        }))
+        .expect("Valid simulated response");
+        raw.map(ScheduleMessageResponse::from)
+    }
+
+    async fn join_conversation(
+        &self,
+        request: &JoinConversationRequest,
+    ) -> SlackApiResponse<JoinConversationResponse> {
+        eprintln!("[dry run] Would join channel {}", request.channel);
+        serde_json::from_value(json!({
+            "ok": true,
+            "channel": {
+                "id": request.channel,
+                "name": request.channel,
+                "is_channel": true,
+                "is_group": false,
+                "is_im": false,
+                "created": 0,
+                "creator": "",
+                "is_archived": false,
+                "is_general": false,
+                "unlinked": 0,
+                "name_normalized": request.channel,
+                "is_shared": false,
+                "is_ext_shared": false,
+                "is_org_shared": false,
+                "pending_shared": [],
+                "is_pending_ext_shared": false,
+                "is_member": true,
+                "is_private": false,
+                "is_mpim": false,
+                "topic": {"value": "", "creator": "", "last_set": 0},
+                "purpose": {"value": "", "creator": "", "last_set": 0},
+                "previous_names": [],
+            }
+        }))
+        .expect("Valid simulated response")
+    }
+
+    async fn user_lookup_by_email(
+        &self,
+        request: &UserLookupRequest,
+    ) -> SlackApiResponse<UserLookupResponse> {
+        self.api.user_lookup_by_email(request).await
+    }
+
+    async fn user_info(&self, request: &UserInfoRequest) -> SlackApiResponse<UserLookupResponse> {
+        self.api.user_info(request).await
+    }
+
+    async fn list_users(&self, request: &UsersListRequest) -> SlackApiResponse<UsersListResponse> {
+        self.api.list_users(request).await
+    }
+
+    async fn list_usergroups(
+        &self,
+        request: &UsergroupsListRequest,
+    ) -> SlackApiResponse<UsergroupsListResponse> {
+        self.api.list_usergroups(request).await
+    }
+
+    async fn list_usergroup_users(
+        &self,
+        request: &UsergroupUsersListRequest,
+    ) -> SlackApiResponse<UsergroupUsersListResponse> {
+        self.api.list_usergroup_users(request).await
+    }
+
+    async fn list_members(
+        &self,
+        request: &ListMembersRequestParams,
+    ) -> SlackApiResponse<ListMembersResponse> {
+        self.api.list_members(request).await
+    }
+
+    async fn list_scheduled_messages(
+        &self,
+        request: &ScheduledMessagesListRequest,
+    ) -> SlackApiResponse<ScheduledMessagesListRaw> {
+        self.api.list_scheduled_messages(request).await
+    }
+
+    async fn delete_scheduled_message(
+        &self,
+        request: &DeleteScheduledMessageRequest,
+    ) -> SlackApiResponse<Empty> {
+        eprintln!("[dry run] Would delete scheduled message {}", request);
+        serde_json::from_value(json!({"ok": true})).expect("Valid simulated response")
+    }
+
+    async fn auth_test(&self) -> SlackApiResponse<BotIdentity> {
+        self.api.auth_test().await
+    }
+
+    fn is_dry_run(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use async_std::task;
+
+    #[test]
+    fn writes_are_simulated() {
+        let api = DryRunSlackApiClient::new(TestSlackClient::default());
+        assert!(api.is_dry_run());
+        let request = ScheduleMessageRequest::new("C1H9RESGL", 1562180400, "<@U1> joke".into());
+        let first = match task::block_on(api.schedule_message(&request)).content {
+            SlackApiContent::Ok(response) => response,
+            SlackApiContent::Err(err) => panic!("{:?}", err),
+        };
+        assert_eq!(first.message.text, "<@U1> joke");
+        assert_eq!(first.post_at.timestamp(), 1562180400);
+        let second = match task::block_on(api.schedule_message(&request)).content {
+            SlackApiContent::Ok(response) => response,
+            SlackApiContent::Err(err) => panic!("{:?}", err),
+        };
+        assert_ne!(first.scheduled_message_id, second.scheduled_message_id);
+
+        let request = DeleteScheduledMessageRequest::new("C1H9RESGL", "Q1298393284");
+        assert!(matches!(
+            task::block_on(api.delete_scheduled_message(&request)).content,
+            SlackApiContent::Ok(_)
+        ));
+        let request = JoinConversationRequest {
+            channel: "C1H9RESGL".to_string(),
+        };
+        assert!(matches!(
+            task::block_on(api.join_conversation(&request)).content,
+            SlackApiContent::Ok(_)
+        ));
+    }
+}
@@ -21,6 +21,8 @@ pub struct ScheduleMessageRequest {
     pub channel: String,
     pub post_at: i64,
     pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<MessageMetadata>,
 }
 
 impl ScheduleMessageRequest {
@@ -29,6 +31,42 @@ impl ScheduleMessageRequest {
             channel: channel.to_string(),
             post_at,
             text,
+            metadata: None,
+        }
+    }
+
+    pub fn with_metadata(mut self, metadata: MessageMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+/// Machine-readable data attached to a message, invisible in the channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageMetadata {
+    pub event_type: String,
+    pub event_payload: serde_json::Value,
+}
+
+/// Event type of the metadata attached to assignment announcements.
+pub const ASSIGNMENT_EVENT_TYPE: &str = "slack_r_assignment";
+
+/// What an announcement assigns. Sent as the payload of its metadata, which stays on the message once posted.
+/// Slack doesn't return the metadata of messages still scheduled, so the bot knows it from its history instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssignmentMetadata {
+    pub role: String,
+    pub target_date: NaiveDate,
+    pub members: Vec<String>,
+    pub seed: u64,
+}
+
+impl From<&AssignmentMetadata> for MessageMetadata {
+    fn from(assignment: &AssignmentMetadata) -> Self {
+        MessageMetadata {
+            event_type: ASSIGNMENT_EVENT_TYPE.to_string(),
+            event_payload: serde_json::to_value(assignment)
+                .expect("Assignment metadata is serializable"),
         }
     }
 }
@@ -118,32 +156,70 @@ pub struct UserLookupRequest {
     pub email: String,
 }
 
+/// Bots and deleted users come without some of the fields, which then take their default value.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserObject {
     pub id: String,
+    #[serde(default)]
     team_id: String,
     pub name: String,
-    deleted: bool,
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
     color: String,
+    #[serde(default)]
     real_name: String,
+    #[serde(default)]
     tz: String,
+    #[serde(default)]
     tz_label: String,
+    #[serde(default)]
     tz_offset: i64,
     pub profile: UserProfile,
+    #[serde(default)]
     is_admin: bool,
+    #[serde(default)]
     is_owner: bool,
+    #[serde(default)]
     is_primary_owner: bool,
-    is_restricted: bool,
-    is_ultra_restricted: bool,
-    is_bot: bool,
+    /// Multi-channel guest.
+    #[serde(default)]
+    pub is_restricted: bool,
+    /// Single-channel guest.
+    #[serde(default)]
+    pub is_ultra_restricted: bool,
+    #[serde(default)]
+    pub is_bot: bool,
     is_stranger: Option<bool>,
+    #[serde(default)]
     updated: u64,
-    is_app_user: bool,
+    #[serde(default)]
+    pub is_app_user: bool,
     is_invited_user: Option<bool>,
     has_2fa: Option<bool>,
     locale: Option<String>,
 }
 
+impl UserObject {
+    /// Display name, falling back to the user name when not set.
+    pub fn display_name(&self) -> String {
+        self.profile
+            .display_name
+            .clone()
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| self.name.clone())
+    }
+
+    /// Whether the @handle, display name or real name is the given name, ignoring case and a leading @.
+    pub fn has_name(&self, name: &str) -> bool {
+        let name = name.trim_start_matches('@').to_lowercase();
+        let display_name = self.profile.display_name.as_deref().unwrap_or_default();
+        [self.name.as_str(), display_name, self.real_name.as_str()]
+            .iter()
+            .any(|candidate| !candidate.is_empty() && candidate.to_lowercase() == name)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserProfile {
     title: Option<String>,
@@ -162,11 +238,17 @@ pub struct UserProfile {
     first_name: Option<String>,
     last_name: Option<String>,
     image_original: Option<String>,
+    #[serde(default)]
     image_24: String,
+    #[serde(default)]
     image_32: String,
+    #[serde(default)]
     image_48: String,
+    #[serde(default)]
     image_72: String,
+    #[serde(default)]
     image_192: String,
+    #[serde(default)]
     image_512: String,
     status_text_canonical: Option<String>,
     team: Option<String>,
@@ -184,6 +266,123 @@ pub struct UserLookupResponse {
     pub team: Option<Team>,
 }
 
+// User Info
+
+#[derive(Debug)]
+pub struct UserInfoEndpoint;
+impl SlackEndpoint for UserInfoEndpoint {
+    type Request = UserInfoRequest;
+    // Same shape as the lookup by email.
+    type Response = UserLookupResponse;
+    fn endpoint_url(&self) -> &str {
+        "users.info"
+    }
+    fn method(&self) -> HttpVerb {
+        HttpVerb::GET
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserInfoRequest {
+    pub user: String,
+}
+
+// Users list
+
+/// Every account of the workspace, the user directory used to find members by @handle or name. Requires the `users:read` scope.
+#[derive(Debug)]
+pub struct UsersListEndpoint;
+impl SlackEndpoint for UsersListEndpoint {
+    type Request = UsersListRequest;
+    type Response = UsersListResponse;
+    fn endpoint_url(&self) -> &str {
+        "users.list"
+    }
+    fn method(&self) -> HttpVerb {
+        HttpVerb::GET
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsersListRequest {
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsersListResponse {
+    pub members: Vec<UserObject>,
+}
+
+// User groups
+
+/// User groups of the workspace, with their @handle. Requires the `usergroups:read` scope.
+#[derive(Debug)]
+pub struct UsergroupsListEndpoint;
+impl SlackEndpoint for UsergroupsListEndpoint {
+    type Request = UsergroupsListRequest;
+    type Response = UsergroupsListResponse;
+    fn endpoint_url(&self) -> &str {
+        "usergroups.list"
+    }
+    fn method(&self) -> HttpVerb {
+        HttpVerb::GET
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsergroupsListRequest {
+    pub include_disabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsergroupObject {
+    pub id: String,
+    /// Mentioned as @handle, without the @.
+    pub handle: String,
+    pub name: String,
+    #[serde(default)]
+    description: String,
+}
+
+impl UsergroupObject {
+    /// Whether the group is the given @handle or ID, ignoring case and a leading @.
+    pub fn is(&self, handle_or_id: &str) -> bool {
+        let handle = handle_or_id.trim_start_matches('@');
+        self.id == handle || self.handle.eq_ignore_ascii_case(handle)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsergroupsListResponse {
+    pub usergroups: Vec<UsergroupObject>,
+}
+
+#[derive(Debug)]
+pub struct UsergroupUsersListEndpoint;
+impl SlackEndpoint for UsergroupUsersListEndpoint {
+    type Request = UsergroupUsersListRequest;
+    type Response = UsergroupUsersListResponse;
+    fn endpoint_url(&self) -> &str {
+        "usergroups.users.list"
+    }
+    fn method(&self) -> HttpVerb {
+        HttpVerb::GET
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsergroupUsersListRequest {
+    /// ID of the group, not its handle.
+    pub usergroup: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsergroupUsersListResponse {
+    /// IDs of the members of the group.
+    pub users: Vec<String>,
+}
+
 #[derive(Debug)]
 pub struct ListMembersEndpoint;
 impl SlackEndpoint for ListMembersEndpoint {
@@ -243,6 +442,9 @@ pub struct ScheduledMessageObject {
     pub post_at: DateTime<Local>,
     date_created: DateTime<Local>,
     text: String,
+    /// What the message assigns, when the local history knows it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    assignment: Option<AssignmentMetadata>,
 }
 
 impl std::fmt::Display for ScheduledMessageObject {
@@ -268,6 +470,7 @@ impl From<&ScheduledMessageObjectRaw> for ScheduledMessageObject {
             post_at: Local.timestamp(raw.post_at, 0),
             date_created: Local.timestamp(raw.date_created, 0),
             text: raw.text.clone(),
+            assignment: None,
         }
     }
 }
@@ -276,6 +479,48 @@ impl ScheduledMessageObject {
     pub fn date(&self) -> chrono::Date<Local> {
         self.post_at.date()
     }
+
+    /// Sets what the message assigns, as recorded when it was scheduled.
+    pub fn with_assignment(mut self, assignment: AssignmentMetadata) -> Self {
+        self.assignment = Some(assignment);
+        self
+    }
+
+    /// What the message assigns. `None` for messages the local history doesn't know of.
+    pub fn assignment(&self) -> Option<AssignmentMetadata> {
+        self.assignment.clone()
+    }
+
+    /// Members assigned by the message: from its known assignment, or else the members mentioned in its text.
+    pub fn assigned_members(&self) -> Vec<String> {
+        match self.assignment() {
+            Some(assignment) => assignment.members,
+            None => self.mentioned_members(),
+        }
+    }
+
+    /// IDs of the users mentioned in the text, as written `<@ID>` or `<@ID|name>`.
+    pub fn mentioned_members(&self) -> Vec<String> {
+        self.text
+            .split("<@")
+            .skip(1)
+            .filter_map(|part| part.split('>').next())
+            .map(|mention| mention.split('|').next().unwrap_or(mention).to_string())
+            .collect()
+    }
+}
+
+/// Scheduled message as Slack lists it, for tests.
+#[cfg(test)]
+pub fn scheduled_message(id: &str, post_at: DateTime<Local>, text: &str) -> ScheduledMessageObject {
+    ScheduledMessageObject {
+        id: id.to_string(),
+        channel_id: "C1H9RESGL".to_string(),
+        post_at,
+        date_created: Local.timestamp(1551891734, 0),
+        text: text.to_string(),
+        assignment: None,
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -390,6 +635,12 @@ impl DeleteScheduledMessageRequest {
     }
 }
 
+impl std::fmt::Display for DeleteScheduledMessageRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} in {}", self.scheduled_message_id, self.channel)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Empty {}
 
@@ -422,6 +673,60 @@ pub struct BotIdentity {
     pub team: String,
     user: String,
     team_id: String,
+    /// ID of the bot as a member of channels.
     pub user_id: String,
     pub bot_id: String,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scheduled_message_mentions() {
+        let raw = ScheduledMessageObjectRaw {
+            channel_id: "C1H9RESGL".to_string(),
+            date_created: 1551891734,
+            id: "1298393284".to_string(),
+            post_at: 1606965300,
+            text: "<@U1> and <@U2|spengler> will be in charge of a joke!".to_string(),
+        };
+        let message = ScheduledMessageObject::from(&raw);
+        assert_eq!(message.mentioned_members(), vec!["U1", "U2"]);
+        assert_eq!(message.assignment(), None);
+        assert_eq!(message.assigned_members(), vec!["U1", "U2"]);
+    }
+
+    #[test]
+    fn scheduled_message_assignment_metadata() {
+        let assignment = AssignmentMetadata {
+            role: "joke".to_string(),
+            target_date: NaiveDate::from_ymd(2022, 2, 15),
+            members: vec!["U3".to_string()],
+            seed: 42,
+        };
+        let request = ScheduleMessageRequest::new("C1H9RESGL", 1606965300, "<@U3>".to_string())
+            .with_metadata(MessageMetadata::from(&assignment));
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["metadata"]["event_type"], "slack_r_assignment");
+        assert_eq!(
+            json["metadata"]["event_payload"]["target_date"],
+            "2022-02-15"
+        );
+
+        // Fields of chat.scheduledMessages.list: no metadata comes back.
+        let raw: ScheduledMessageObjectRaw = serde_json::from_value(serde_json::json!({
+            "channel_id": "C1H9RESGL",
+            "date_created": 1551891734,
+            "id": "1298393284",
+            "post_at": 1606965300,
+            "text": "<@U1> will be in charge of a joke!",
+        }))
+        .unwrap();
+        let message = ScheduledMessageObject::from(&raw);
+        assert_eq!(message.assignment(), None);
+        let message = message.with_assignment(assignment.clone());
+        assert_eq!(message.assignment(), Some(assignment));
+        assert_eq!(message.assigned_members(), vec!["U3"]);
+    }
+}
@@ -42,9 +42,25 @@ where
     ) -> SlackApiResponse<Self::Response> {
         info!("Calling {:?}", self.endpoint_url());
         let request = self.build_request(client, &request);
-        let raw = request.recv_string().await.unwrap();
+        let raw = match request.recv_string().await {
+            Ok(raw) => raw,
+            Err(err) => {
+                error!("Request to {} failed: {}", self.endpoint_url(), err);
+                return SlackApiResponse::from_error(SlackApiError::connection_failed);
+            }
+        };
         debug!("Raw response: {}", raw);
-        let response: SlackApiResponse<Self::Response> = serde_json::from_str(&raw).unwrap();
+        let response: SlackApiResponse<Self::Response> = match serde_json::from_str(&raw) {
+            Ok(response) => response,
+            Err(err) => {
+                error!(
+                    "Couldn't read the response of {}: {}",
+                    self.endpoint_url(),
+                    err
+                );
+                return SlackApiResponse::from_error(SlackApiError::connection_failed);
+            }
+        };
         debug!("Serialized response: {:?}", response);
         match &response.content {
             SlackApiContent::Ok(_ok) => info!("Got Slack response successfully"),
@@ -138,6 +154,8 @@ pub enum SlackApiError {
     internal_error,
     /// Not found
     method_not_found,
+    /// Not sent by Slack: the request couldn't reach it, or its response couldn't be read.
+    connection_failed,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -177,11 +195,26 @@ pub enum SlackApiContent<T> {
 }
 
 impl<T> SlackApiResponse<T> {
-    /// Mimick a the Rust's Result type
-    pub(crate) fn unwrap(self) -> T {
-        match self.content {
-            SlackApiContent::Ok(value) => value,
-            SlackApiContent::Err(error) => panic!("{:?}", error),
+    /// Failed response built locally, for errors that happen before Slack can respond.
+    fn from_error(error: SlackApiError) -> SlackApiResponse<T> {
+        SlackApiResponse {
+            ok: false,
+            content: SlackApiContent::Err(SlackApiErrorResponse { error }),
+            warning: None,
+            response_metadata: None,
+        }
+    }
+
+    /// Converts the successful content, keeping errors and metadata as is.
+    pub(crate) fn map<U>(self, f: impl FnOnce(T) -> U) -> SlackApiResponse<U> {
+        SlackApiResponse {
+            ok: self.ok,
+            content: match self.content {
+                SlackApiContent::Ok(value) => SlackApiContent::Ok(f(value)),
+                SlackApiContent::Err(error) => SlackApiContent::Err(error),
+            },
+            warning: self.warning,
+            response_metadata: self.response_metadata,
         }
     }
 }
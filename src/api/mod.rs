@@ -1,8 +1,10 @@
 mod client;
+mod dry_run;
 mod endpoints;
 mod generic;
-use chrono::{DateTime, Local, TimeZone};
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
 pub(crate) use client::{ProdSlackApiClient, SlackApiClient};
+pub(crate) use dry_run::DryRunSlackApiClient;
 pub use endpoints::*;
 pub use generic::*;
 use log::{debug, error, info};
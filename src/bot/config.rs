@@ -2,53 +2,63 @@ use super::*;
 use crate::{SlackRError, CONFIG_FILE_PATH_ENV_VAR, DEFAULT_CONFIG_PATH};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     env,
     fs::{write, File},
     io::{self, Read},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BotConfig {
-    /// The list of members ids that can be selected. Adds all of the channel when added to a channel.
-    pub members: Vec<String>,
-    /// Members that have been selected.
-    pub selected: Vec<String>,
+    /// Settings and rotation state of the joke role, kept at the top level of the file.
+    #[serde(flatten)]
+    pub joke: RoleConfig,
     /// The channel on which this bot will post. Single channel per config.
     /// You may  have a different config file for different channels, although this behaviour is untested yet.
     pub channel: String,
-    /// As input only accepts dates, this is the time that will be applied to the input date.
-    pub target_time: NaiveTime,
-    /// Possible offset for the actual time at which the message will be posted, to give some leeway for the joke to be prepared.
-    /// How many days in avance to schedule the post, relative to the target time.
-    pub advance_days: i64,
-    /// On the day from `advance_days`, post at this time.
-    pub post_time: NaiveTime,
     /// Delay for "instant" schedules, such as the reroll. Defaults to 45s.
     pub instant_delay: i64,
     /// Slack API token for the bot.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub token: Option<String>,
     // Bot self Id, acquired as a check for the token, and making sure it never adds itself as member.
     pub id: String,
+    /// Appends the draw seed to the scheduled message, so anyone in the channel can check the pick.
+    #[serde(default)]
+    pub announce_seed: bool,
+    /// Language of the default templates and of the dates in announcements.
+    #[serde(default)]
+    pub locale: Locale,
+    /// Other rotating duties, by name, using the same scheduling as the joke.
+    #[serde(default)]
+    pub roles: BTreeMap<String, RoleConfig>,
 }
 
 impl Default for BotConfig {
     fn default() -> Self {
         BotConfig {
-            members: Vec::new(),
-            selected: Vec::new(),
+            joke: RoleConfig::default(),
             channel: String::new(),
-            target_time: NaiveTime::from_hms(11, 30, 0),
-            post_time: NaiveTime::from_hms(11, 30, 0),
-            advance_days: 1,
             instant_delay: 45,
             token: None,
             id: String::new(),
+            announce_seed: false,
+            locale: Locale::default(),
+            roles: BTreeMap::new(),
         }
     }
 }
 
 impl BotConfig {
+    /// Copy of the config without the API token, safe to print or pipe into other tools.
+    pub fn redacted(&self) -> BotConfig {
+        BotConfig {
+            token: None,
+            ..self.clone()
+        }
+    }
+
     pub fn new() -> BotConfig {
         info!(
             "Reading config path from {} env var",
@@ -101,6 +111,15 @@ impl BotConfig {
         }
     }
 
+    /// Reads the config file again, keeping errors for the caller instead of falling back to the default config.
+    pub fn reload(path: &Path) -> Result<BotConfig, SlackRError> {
+        let file = File::open(path).map_err(|err| {
+            error!("Couldn't open config file at {:?}. {}", path, err);
+            SlackRError::CorruptedConfig
+        })?;
+        BotConfig::from_file(file)
+    }
+
     /// Will read a given file handle, expected to contain the config in JSON format, and try to construct the config from it.
     /// Errors will be ignored and simply ignore the file and return the default values, with warnings.
     fn from_file(mut file: File) -> Result<Self, SlackRError> {
@@ -121,10 +140,46 @@ impl BotConfig {
             error!("Failed parsing config file. {}", err);
             SlackRError::CorruptedConfig
         })?;
+        for name in config.role_names() {
+            let role = config.role(name).expect("Listed role");
+            let plural = role.members_per_assignment > 1;
+            role.message_templates(name, config.locale, plural)
+                .map_err(|err| {
+                    error!(
+                        "Invalid message template for role {} in config file. {}",
+                        name, err
+                    );
+                    SlackRError::InvalidTemplate
+                })?;
+        }
         info!("Successfully read config from file");
         Ok(config)
     }
 
+    /// Gets a role by name, the joke role being the top level of the config.
+    pub fn role(&self, name: &str) -> Option<&RoleConfig> {
+        if name == JOKE_ROLE {
+            Some(&self.joke)
+        } else {
+            self.roles.get(name)
+        }
+    }
+
+    pub fn role_mut(&mut self, name: &str) -> Option<&mut RoleConfig> {
+        if name == JOKE_ROLE {
+            Some(&mut self.joke)
+        } else {
+            self.roles.get_mut(name)
+        }
+    }
+
+    /// Names of all the roles, starting with the joke role.
+    pub fn role_names(&self) -> Vec<&str> {
+        let mut names = vec![JOKE_ROLE];
+        names.extend(self.roles.keys().map(|name| name.as_str()));
+        names
+    }
+
     // Writes config to file.
     pub fn to_file(&self) -> Result<(), SlackRError> {
         let path = Self::get_config_path();
@@ -0,0 +1,257 @@
+use super::history::{HistoryEntry, HistoryStatus};
+use chrono::{Duration, NaiveDate, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+/// File formats of the schedule export.
+/// In CSV, members and their names are separated by `;`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// One line per assignment, whatever its status.
+    Csv,
+    /// iCalendar, with an all-day event per target date of the standing assignments, to subscribe to in calendar apps.
+    Ics,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "ics" => Ok(ExportFormat::Ics),
+            _ => Err(format!(
+                "Unknown export format {}. Available: csv, ics",
+                input
+            )),
+        }
+    }
+}
+
+/// Clap validator for the export format.
+pub fn validate_export_format_input(input: String) -> Result<(), String> {
+    input.parse::<ExportFormat>().map(|_format| ())
+}
+
+const CSV_HEADER: &str = "id,status,role,target_date,post_at,members,names,channel,seed";
+
+impl ExportFormat {
+    /// `names` maps member IDs to display names. `labels` maps role names to their label.
+    pub fn render(
+        &self,
+        entries: &[HistoryEntry],
+        names: &HashMap<String, String>,
+        labels: &HashMap<String, String>,
+    ) -> String {
+        match self {
+            ExportFormat::Csv => to_csv(entries, names),
+            ExportFormat::Ics => to_ics(entries, names, labels),
+        }
+    }
+}
+
+fn to_csv(entries: &[HistoryEntry], names: &HashMap<String, String>) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for entry in entries {
+        let fields = [
+            entry.id.clone(),
+            status_name(entry.status).to_string(),
+            entry.role.clone().unwrap_or_default(),
+            entry
+                .target_date
+                .map(|date| date.naive_local().date().to_string())
+                .unwrap_or_default(),
+            entry.post_at.to_rfc3339(),
+            entry.members.join(";"),
+            member_names(entry, names).join(";"),
+            entry.channel.clone(),
+            entry.seed.map(|seed| seed.to_string()).unwrap_or_default(),
+        ];
+        let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// One event per target date, naming the members of each role assigned that day.
+/// Only assignments still standing, scheduled or posted, count.
+fn to_ics(
+    entries: &[HistoryEntry],
+    names: &HashMap<String, String>,
+    labels: &HashMap<String, String>,
+) -> String {
+    let mut days: BTreeMap<NaiveDate, Vec<String>> = BTreeMap::new();
+    for entry in entries {
+        if !matches!(
+            entry.status,
+            HistoryStatus::Scheduled | HistoryStatus::Posted
+        ) {
+            continue;
+        }
+        let date = entry
+            .target_date
+            .unwrap_or(entry.post_at)
+            .naive_local()
+            .date();
+        let role = entry.role.as_deref().unwrap_or("assignment");
+        let label = labels.get(role).map(String::as_str).unwrap_or(role);
+        days.entry(date).or_default().push(format!(
+            "{}: {}",
+            label,
+            member_names(entry, names).join(", ")
+        ));
+    }
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//slack-r//schedule//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    for (date, assignments) in days {
+        lines.extend(vec![
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}@slack-r", date.format("%Y%m%d")),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+            format!(
+                "DTEND;VALUE=DATE:{}",
+                (date + Duration::days(1)).format("%Y%m%d")
+            ),
+            format!("SUMMARY:{}", ics_text(&assignments.join("; "))),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+    // iCalendar lines end with CRLF.
+    let lines: Vec<String> = lines.iter().map(|line| fold_ics_line(line)).collect();
+    lines.join("\r\n") + "\r\n"
+}
+
+/// Splits a content line longer than 75 octets, continuing on the next lines after a space, as RFC 5545 requires.
+/// Never splits inside a UTF-8 character.
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+fn status_name(status: HistoryStatus) -> &'static str {
+    match status {
+        HistoryStatus::Scheduled => "scheduled",
+        HistoryStatus::Cancelled => "cancelled",
+        HistoryStatus::Rerolled => "rerolled",
+        HistoryStatus::Posted => "posted",
+    }
+}
+
+/// Display names of the members, falling back to their ID.
+fn member_names(entry: &HistoryEntry, names: &HashMap<String, String>) -> Vec<String> {
+    entry
+        .members
+        .iter()
+        .map(|member| names.get(member).unwrap_or(member).clone())
+        .collect()
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bot::history::history_entry;
+    use chrono::{Local, TimeZone};
+
+    fn entries() -> Vec<HistoryEntry> {
+        let target_date = Local.ymd(2022, 2, 15).and_hms(11, 30, 0);
+        vec![
+            history_entry("Q1", &["U1", "U2"], target_date, HistoryStatus::Scheduled),
+            history_entry("Q2", &["U1", "U2"], target_date, HistoryStatus::Cancelled),
+        ]
+    }
+
+    fn names() -> HashMap<String, String> {
+        let mut names = HashMap::new();
+        names.insert("U1".to_string(), "Egon, PhD".to_string());
+        names
+    }
+
+    #[test]
+    fn csv_has_a_line_per_entry() {
+        let csv = to_csv(&entries(), &names());
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[1].starts_with("Q1,scheduled,joke,2022-02-15,"));
+        assert!(lines[1].ends_with(",U1;U2,\"Egon, PhD;U2\",test_channel,42"));
+        assert!(lines[2].starts_with("Q2,cancelled,"));
+    }
+
+    #[test]
+    fn ics_has_an_all_day_event_per_target_date() {
+        let mut labels = HashMap::new();
+        labels.insert("joke".to_string(), "joke teller".to_string());
+        let mut entries = entries();
+        entries.push(HistoryEntry {
+            role: Some("standup".to_string()),
+            ..history_entry(
+                "Q3",
+                &["U3"],
+                entries[0].target_date.unwrap(),
+                HistoryStatus::Scheduled,
+            )
+        });
+        let ics = to_ics(&entries, &names(), &labels);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ics.contains("UID:20220215@slack-r\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20220215\r\nDTEND;VALUE=DATE:20220216\r\n"));
+        assert!(ics.contains("SUMMARY:joke teller: Egon\\, PhD\\, U2\\; standup: U3\r\n"));
+    }
+
+    #[test]
+    fn ics_folds_long_lines() {
+        let members: Vec<String> = (0..12).map(|i| format!("U{:08}", i)).collect();
+        let members: Vec<&str> = members.iter().map(String::as_str).collect();
+        let target_date = Local.ymd(2022, 2, 15).and_hms(11, 30, 0);
+        let entry = history_entry("Q1", &members, target_date, HistoryStatus::Scheduled);
+        let mut names = HashMap::new();
+        names.insert("U00000000".to_string(), "Égon Spëngler".to_string());
+        let ics = to_ics(&[entry], &names, &HashMap::new());
+        for line in ics.split("\r\n") {
+            assert!(line.len() <= 75, "Line too long: {}", line);
+        }
+        let unfolded = ics.replace("\r\n ", "");
+        let summary = format!(
+            "SUMMARY:joke: Égon Spëngler\\, {}\r\n",
+            members[1..].join("\\, ")
+        );
+        assert!(unfolded.contains(&summary));
+        assert!(ics.contains("\r\n "));
+    }
+}
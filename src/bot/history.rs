@@ -0,0 +1,241 @@
+use crate::{
+    api::AssignmentMetadata, SlackRError, DEFAULT_HISTORY_PATH, HISTORY_FILE_PATH_ENV_VAR,
+};
+use chrono::{DateTime, Local};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+/// State of an assignment message at the time of the entry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryStatus {
+    Scheduled,
+    Cancelled,
+    /// Replaced by another message, through a reroll or a swap.
+    Rerolled,
+    Posted,
+}
+
+/// A line of the history file, recording an action of the bot on an assignment message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// When the entry was written.
+    pub recorded_at: DateTime<Local>,
+    /// ID of the scheduled message. The last entry of an ID holds its current status.
+    pub id: String,
+    pub status: HistoryStatus,
+    pub channel: String,
+    /// Unknown for messages the bot didn't schedule itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    pub members: Vec<String>,
+    /// Unknown for messages the bot didn't schedule itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_date: Option<DateTime<Local>>,
+    pub post_at: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl HistoryEntry {
+    /// What the message assigns, for messages the bot scheduled itself.
+    /// Imported entries don't know their seed, and may not know their role nor target date.
+    pub fn assignment(&self) -> Option<AssignmentMetadata> {
+        Some(AssignmentMetadata {
+            role: self.role.clone()?,
+            target_date: self.target_date?.naive_local().date(),
+            members: self.members.clone(),
+            seed: self.seed?,
+        })
+    }
+
+    /// Copy of the entry with a new status, recorded now.
+    pub fn with_status(&self, status: HistoryStatus) -> HistoryEntry {
+        HistoryEntry {
+            recorded_at: Local::now(),
+            status,
+            ..self.clone()
+        }
+    }
+}
+
+/// Entry of a joke assignment scheduled by the bot, posted on its target date, for tests.
+#[cfg(test)]
+pub(crate) fn history_entry(
+    id: &str,
+    members: &[&str],
+    target_date: DateTime<Local>,
+    status: HistoryStatus,
+) -> HistoryEntry {
+    HistoryEntry {
+        recorded_at: target_date,
+        id: id.to_string(),
+        status,
+        channel: "test_channel".to_string(),
+        role: Some("joke".to_string()),
+        members: members.iter().map(|member| member.to_string()).collect(),
+        target_date: Some(target_date),
+        post_at: target_date,
+        seed: Some(42),
+    }
+}
+
+impl std::fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let target = match self.target_date {
+            Some(date) => date.naive_local().date().to_string(),
+            None => "unknown date".to_string(),
+        };
+        write!(
+            f,
+            "{:?} {} as {} on {}, posted at {}. Schedule ID: {}",
+            self.status,
+            self.members.join(", "),
+            self.role.as_deref().unwrap_or("unknown role"),
+            target,
+            self.post_at,
+            self.id
+        )
+    }
+}
+
+/// Append-only local history of the assignments, one JSON entry per line.
+/// Entries are never rewritten: a status change is a new entry for the same message ID.
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(path: PathBuf) -> History {
+        History { path }
+    }
+
+    /// Uses the path from the environment variable, or the default one.
+    pub fn from_env() -> History {
+        let path = match env::var(HISTORY_FILE_PATH_ENV_VAR) {
+            Ok(path_string) => {
+                info!(
+                    "Env var {} set. Using history path {}.",
+                    HISTORY_FILE_PATH_ENV_VAR, path_string
+                );
+                PathBuf::from(path_string)
+            }
+            Err(_) => PathBuf::from(DEFAULT_HISTORY_PATH),
+        };
+        History::new(path)
+    }
+
+    pub fn append(&self, entries: &[HistoryEntry]) -> Result<(), SlackRError> {
+        debug!("Appending {} entries to {:?}", entries.len(), self.path);
+        let mut lines = String::new();
+        for entry in entries {
+            lines.push_str(&serde_json::to_string(entry).expect("Couldn't serialize history"));
+            lines.push('\n');
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .map_err(|e| {
+                error!("Couldn't write history to {:?}. Error: {}", self.path, e);
+                SlackRError::WriteHistory
+            })
+    }
+
+    /// All entries, oldest first. A missing file is an empty history, and broken lines are skipped.
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    error!("Couldn't read history at {:?}. Error: {}", self.path, err);
+                }
+                return Vec::new();
+            }
+        };
+        BufReader::new(file)
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let line = line.ok()?;
+                if line.trim().is_empty() {
+                    return None;
+                }
+                serde_json::from_str(&line)
+                    .map_err(|e| warn!("Skipping history line {}: {}", index + 1, e))
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Current state of each message: its last entry, in the order messages were first recorded.
+    pub fn current(&self) -> Vec<HistoryEntry> {
+        latest_by_id(self.entries())
+    }
+
+    /// Last entry recorded for the message.
+    pub fn find(&self, id: &str) -> Option<HistoryEntry> {
+        self.entries()
+            .into_iter()
+            .rev()
+            .find(|entry| entry.id == id)
+    }
+}
+
+fn latest_by_id(entries: Vec<HistoryEntry>) -> Vec<HistoryEntry> {
+    let mut current: Vec<HistoryEntry> = Vec::new();
+    for entry in entries {
+        match current.iter_mut().find(|known| known.id == entry.id) {
+            Some(known) => *known = entry,
+            None => current.push(entry),
+        }
+    }
+    current
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(id: &str, status: HistoryStatus) -> HistoryEntry {
+        history_entry(
+            id,
+            &["user_1"],
+            Local.ymd(2022, 2, 15).and_hms(11, 30, 0),
+            status,
+        )
+    }
+
+    #[test]
+    fn append_and_read_latest_status() {
+        let path = env::temp_dir().join(format!("slack-r-history-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let history = History::new(path.clone());
+        assert!(history.entries().is_empty());
+
+        let first = entry("Q1", HistoryStatus::Scheduled);
+        history
+            .append(&[first.clone(), entry("Q2", HistoryStatus::Scheduled)])
+            .unwrap();
+        history
+            .append(&[first.with_status(HistoryStatus::Cancelled)])
+            .unwrap();
+
+        assert_eq!(history.entries().len(), 3);
+        let current = history.current();
+        assert_eq!(current.len(), 2);
+        assert_eq!(current[0].id, "Q1");
+        assert_eq!(current[0].status, HistoryStatus::Cancelled);
+        assert_eq!(current[1].status, HistoryStatus::Scheduled);
+        assert_eq!(history.find("Q1").unwrap().status, HistoryStatus::Cancelled);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,421 @@
+use crate::api::UserObject;
+use log::{error, info};
+use serde::Serialize;
+use std::fmt::Display;
+use std::path::Path;
+
+/// Whether the input looks like a Slack user ID, such as U023BECGF or W012A3CDE.
+pub fn is_user_id(input: &str) -> bool {
+    input.len() >= 9
+        && (input.starts_with('U') || input.starts_with('W'))
+        && input
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+/// Whether the input looks like an email, as opposed to an @handle.
+pub fn is_email(input: &str) -> bool {
+    match input.split_once('@') {
+        Some((user, domain)) => !user.is_empty() && domain.contains('.'),
+        None => false,
+    }
+}
+
+/// Emails and Slack IDs found in the cells of a CSV file, each once, in order.
+/// Cells can be separated by commas or semicolons. Other cells, such as headers or names, are ignored.
+pub fn parse_member_csv(content: &str) -> Vec<String> {
+    let mut members: Vec<String> = Vec::new();
+    for cell in content.lines().flat_map(|line| line.split([',', ';'])) {
+        let cell = cell.trim().trim_matches('"').trim();
+        if (is_email(cell) || is_user_id(cell)) && !members.iter().any(|m| m == cell) {
+            members.push(cell.to_string());
+        }
+    }
+    members
+}
+
+/// Reads the emails and Slack IDs of a CSV file, nothing if it can't be read.
+pub fn read_member_csv(path: &Path) -> Vec<String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => {
+            let members = parse_member_csv(&content);
+            info!("Found {} emails and IDs in {:?}", members.len(), path);
+            members
+        }
+        Err(err) => {
+            error!("Couldn't read members file {:?}: {}", path, err);
+            Vec::new()
+        }
+    }
+}
+
+/// Why an account of the channel isn't imported as a member.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionReason {
+    Deleted,
+    Bot,
+    AppUser,
+    /// Restricted or ultra restricted account, such as a guest.
+    Guest,
+}
+
+/// Kinds of accounts imported anyway. By default, only full, active, human members are imported.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ImportFilter {
+    pub include_deleted: bool,
+    pub include_bots: bool,
+    pub include_app_users: bool,
+    pub include_guests: bool,
+}
+
+impl ImportFilter {
+    /// Why the user shouldn't be imported, if they shouldn't.
+    pub fn exclusion_reason(&self, user: &UserObject) -> Option<ExclusionReason> {
+        if user.deleted && !self.include_deleted {
+            Some(ExclusionReason::Deleted)
+        } else if user.is_bot && !self.include_bots {
+            Some(ExclusionReason::Bot)
+        } else if user.is_app_user && !self.include_app_users {
+            Some(ExclusionReason::AppUser)
+        } else if (user.is_restricted || user.is_ultra_restricted) && !self.include_guests {
+            Some(ExclusionReason::Guest)
+        } else {
+            None
+        }
+    }
+}
+
+/// An account of the channel left out of the members.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Exclusion {
+    pub id: String,
+    pub name: String,
+    pub reason: ExclusionReason,
+}
+
+impl Display for Exclusion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (reason, flag) = match self.reason {
+            ExclusionReason::Deleted => ("deactivated account", "--include-deleted"),
+            ExclusionReason::Bot => ("bot", "--include-bots"),
+            ExclusionReason::AppUser => ("app user", "--include-apps"),
+            ExclusionReason::Guest => ("guest account", "--include-guests"),
+        };
+        write!(
+            f,
+            "Not imported: {} ({}), {}. Import with {}",
+            self.id, self.name, reason, flag
+        )
+    }
+}
+
+/// A member added or removed, with their display name when Slack gave it.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct MemberChange {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Display for MemberChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} ({})", self.id, name),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+/// A member of a role, as listed by `members list`. Printed as JSON with `--output json`.
+#[derive(Debug, Serialize)]
+pub struct MemberInfo {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Out of the draw until enabled again.
+    pub disabled: bool,
+    /// Already had their turn in the current rotation.
+    pub selected: bool,
+}
+
+impl Display for MemberInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<12} {}",
+            self.id,
+            self.name.as_deref().unwrap_or("(unknown name)")
+        )?;
+        if self.disabled {
+            write!(f, ", disabled")?;
+        }
+        if self.selected {
+            write!(f, ", had their turn")?;
+        }
+        Ok(())
+    }
+}
+
+/// Change made to a member of a role.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberAction {
+    Removed,
+    Disabled,
+    Enabled,
+}
+
+/// Result of `members remove`, `disable` or `enable` for a member. Printed as JSON with `--output json`.
+#[derive(Debug, Serialize)]
+pub struct MemberUpdate {
+    pub role: String,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub action: MemberAction,
+}
+
+impl Display for MemberUpdate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let member = MemberChange {
+            id: self.id.clone(),
+            name: self.name.clone(),
+        };
+        match self.action {
+            MemberAction::Removed => write!(f, "Removed {} from {}", member, self.role),
+            MemberAction::Disabled => write!(f, "Disabled {} in {}", member, self.role),
+            MemberAction::Enabled => write!(f, "Enabled {} in {}", member, self.role),
+        }
+    }
+}
+
+/// Result of `add members`, a bulk import of emails or IDs. Printed as JSON with `--output json`.
+#[derive(Debug, Default, Serialize)]
+pub struct ImportSummary {
+    /// Role the members are added to.
+    pub role: String,
+    /// Found on Slack and not yet in the role.
+    pub found: Vec<MemberChange>,
+    /// Emails or IDs Slack doesn't know, or the bot isn't allowed to see.
+    pub not_found: Vec<String>,
+    /// Already members of the role.
+    pub already_present: Vec<MemberChange>,
+    /// Whether the found members were confirmed and added to the config.
+    pub applied: bool,
+}
+
+impl ImportSummary {
+    /// Lines of the summary, `+` for members to add, `=` for those already present and `?` for those not found.
+    pub fn summary(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        lines.extend(self.found.iter().map(|member| format!("+ {}", member)));
+        lines.extend(
+            self.already_present
+                .iter()
+                .map(|member| format!("= {} already present", member)),
+        );
+        lines.extend(
+            self.not_found
+                .iter()
+                .map(|input| format!("? {} not found", input)),
+        );
+        lines.join("\n")
+    }
+}
+
+impl Display for ImportSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.found.is_empty() || !self.already_present.is_empty() || !self.not_found.is_empty()
+        {
+            writeln!(f, "{}", self.summary())?;
+        }
+        let added = if self.applied { self.found.len() } else { 0 };
+        write!(
+            f,
+            "Added {} members to {}: {} found, {} not found, {} already present.",
+            added,
+            self.role,
+            self.found.len(),
+            self.not_found.len(),
+            self.already_present.len()
+        )?;
+        if !self.found.is_empty() && !self.applied {
+            write!(f, " Changes not applied.")?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Difference between the members of the config and of the channel. Printed as JSON with `--output json`.
+#[derive(Debug, Default, Serialize)]
+pub struct MembersDiff {
+    /// Role the new members are added to.
+    pub role: String,
+    /// In the channel, not yet in the role.
+    pub added: Vec<MemberChange>,
+    /// In any role, no longer in the channel. Removed from every role and rotation.
+    pub removed: Vec<MemberChange>,
+    /// In the channel, but left out by the import filter.
+    pub excluded: Vec<Exclusion>,
+    /// Whether the changes were confirmed and applied to the config.
+    pub applied: bool,
+}
+
+impl MembersDiff {
+    /// `role_members` are the members of the role to add to, `all_members` those of every role.
+    /// The bot itself is never added.
+    pub fn compute(
+        role_name: &str,
+        role_members: &[String],
+        all_members: &[String],
+        channel_members: &[String],
+        bot_id: &str,
+    ) -> MembersDiff {
+        let added = channel_members
+            .iter()
+            .filter(|member| *member != bot_id && !role_members.contains(member))
+            .map(|member| MemberChange {
+                id: member.clone(),
+                name: None,
+            })
+            .collect();
+        let mut removed: Vec<MemberChange> = Vec::new();
+        for member in all_members {
+            if !channel_members.contains(member) && !removed.iter().any(|m| &m.id == member) {
+                removed.push(MemberChange {
+                    id: member.clone(),
+                    name: None,
+                });
+            }
+        }
+        MembersDiff {
+            role: role_name.to_string(),
+            added,
+            removed,
+            excluded: Vec::new(),
+            applied: false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Lines of the diff, `+` for additions and `-` for removals.
+    pub fn summary(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        lines.extend(self.added.iter().map(|member| format!("+ {}", member)));
+        lines.extend(self.removed.iter().map(|member| format!("- {}", member)));
+        lines.join("\n")
+    }
+}
+
+impl Display for MembersDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for exclusion in &self.excluded {
+            writeln!(f, "{}", exclusion)?;
+        }
+        if self.is_empty() {
+            return writeln!(f, "Members of {} already match the channel.", self.role);
+        }
+        writeln!(f, "{}", self.summary())?;
+        if self.applied {
+            writeln!(
+                f,
+                "Added {} members to {}, removed {} members from every role.",
+                self.added.len(),
+                self.role,
+                self.removed.len()
+            )
+        } else {
+            writeln!(f, "Changes not applied.")
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ids(members: &[&str]) -> Vec<String> {
+        members.iter().map(|m| m.to_string()).collect()
+    }
+
+    fn user(flags: serde_json::Value) -> UserObject {
+        let mut user = serde_json::json!({
+            "id": "B1",
+            "name": "deploy-bot",
+            "profile": {"display_name": ""},
+        });
+        user.as_object_mut()
+            .unwrap()
+            .extend(flags.as_object().unwrap().clone());
+        serde_json::from_value(user).unwrap()
+    }
+
+    #[test]
+    fn import_filter_excludes_non_members() {
+        let filter = ImportFilter::default();
+        let bot = user(serde_json::json!({"is_bot": true}));
+        assert_eq!(bot.display_name(), "deploy-bot");
+        assert_eq!(filter.exclusion_reason(&bot), Some(ExclusionReason::Bot));
+        let deleted_bot = user(serde_json::json!({"is_bot": true, "deleted": true}));
+        assert_eq!(
+            filter.exclusion_reason(&deleted_bot),
+            Some(ExclusionReason::Deleted)
+        );
+        let guest = user(serde_json::json!({"is_ultra_restricted": true}));
+        assert_eq!(
+            filter.exclusion_reason(&guest),
+            Some(ExclusionReason::Guest)
+        );
+        let app = user(serde_json::json!({"is_app_user": true}));
+        assert_eq!(
+            filter.exclusion_reason(&app),
+            Some(ExclusionReason::AppUser)
+        );
+        assert_eq!(filter.exclusion_reason(&user(serde_json::json!({}))), None);
+
+        let include_bots = ImportFilter {
+            include_bots: true,
+            ..ImportFilter::default()
+        };
+        assert_eq!(include_bots.exclusion_reason(&bot), None);
+        assert_eq!(
+            include_bots.exclusion_reason(&deleted_bot),
+            Some(ExclusionReason::Deleted)
+        );
+    }
+
+    #[test]
+    fn members_from_csv() {
+        let content = "name;email\n\
+            Egon;spengler@ghostbusters.example.com\n\
+            \"Ray\", U061F7AUR, @stantz\n\
+            Egon again;spengler@ghostbusters.example.com\n";
+        assert_eq!(
+            parse_member_csv(content),
+            ids(&["spengler@ghostbusters.example.com", "U061F7AUR"])
+        );
+        assert!(!is_email("@stantz"));
+        assert!(!is_email("jokes@team"));
+    }
+
+    #[test]
+    fn diff_with_channel_members() {
+        let diff = MembersDiff::compute(
+            "joke",
+            &ids(&["a", "gone"]),
+            &ids(&["a", "gone", "standup_only", "left"]),
+            &ids(&["a", "standup_only", "new", "bot"]),
+            "bot",
+        );
+        let added: Vec<&str> = diff.added.iter().map(|m| m.id.as_str()).collect();
+        let removed: Vec<&str> = diff.removed.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(added, vec!["standup_only", "new"]);
+        assert_eq!(removed, vec!["gone", "left"]);
+        assert_eq!(diff.summary(), "+ standup_only\n+ new\n- gone\n- left");
+        assert!(!diff.applied);
+    }
+}
@@ -1,31 +1,163 @@
 mod config;
+mod export;
+mod history;
+mod members;
+mod prompt;
+mod role;
+mod schedule;
+mod stats;
+mod sync;
+mod template;
 use crate::{
     // SlackRError,
     api::{
-        self, ListMembersRequestParams, ProdSlackApiClient, ScheduledMessageObject,
-        ScheduledMessagesListRequest, SlackApiClient, SlackApiContent, SlackApiError,
-        SlackApiWarning,
+        self, AssignmentMetadata, ListMembersRequestParams, MessageMetadata, ProdSlackApiClient,
+        ScheduledMessageObject, ScheduledMessagesListRequest, SlackApiClient, SlackApiContent,
+        SlackApiError, SlackApiWarning, UserInfoRequest, UserObject, UsergroupUsersListRequest,
+        UsergroupsListRequest, UsersListRequest,
     },
-    dates::convert_date_string_to_local,
+    dates::{convert_date_string_to_local, Locale},
 };
-use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, Weekday};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Weekday};
 pub use config::BotConfig;
+pub use export::{validate_export_format_input, ExportFormat};
+use futures::stream::{self, StreamExt};
+pub use history::History;
+pub use history::HistoryEntry;
+use history::HistoryStatus;
 use log::{debug, error, info, warn};
-use rand::seq::SliceRandom;
+use members::{
+    is_email, is_user_id, Exclusion, ImportSummary, MemberAction, MemberChange, MemberInfo,
+    MemberUpdate, MembersDiff,
+};
+pub use members::{read_member_csv, ImportFilter};
+pub use prompt::Prompt;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+pub use role::JOKE_ROLE;
+use role::{RoleConfig, UsergroupRef};
+pub use schedule::{validate_schedule_input, validate_slot_count_input};
+use serde::Serialize;
+pub use stats::Stats;
+use std::collections::HashMap;
 use std::fmt::Display;
+use sync::{reconcile, SyncChange, SyncKind};
+pub use template::validate_template_input;
+use template::{default_template, MessageContext, MessageTemplate, Placeholder};
+
+/// How many user lookups run at the same time during bulk imports, to stay clear of Slack's rate limits.
+const LOOKUP_CONCURRENCY: usize = 8;
+
+/// Options of a scheduling run, from the `joke` and `rota` arguments.
+#[derive(Default)]
+pub struct ScheduleOptions<'a> {
+    /// Target dates, as YYYY-MM-DD. Defaults to the next slot of the role's schedule, or to tomorrow without one.
+    pub days: Vec<&'a str>,
+    /// How many upcoming slots to keep assigned when no day is given. Slots of a role without schedule are weekdays.
+    pub next: Option<usize>,
+    /// Day to post on, as YYYY-MM-DD, instead of the one calculated from the target date.
+    pub post_on: Option<&'a str>,
+    /// Seed of the draw, instead of the one derived from the target date.
+    pub seed: Option<u64>,
+    /// How many members to assign together, instead of the role's setting.
+    pub members_per_assignment: Option<usize>,
+    /// Members to assign instead of drawing them, as ID, email or name.
+    pub members: Vec<&'a str>,
+}
 
-pub struct JokeSuccess {
+/// Result of a scheduled assignment. Printed as JSON with `--output json`, so fields are only added, never renamed.
+#[derive(Serialize)]
+pub struct Assignment {
+    role: String,
+    members: Vec<String>,
     message: String,
     target_date: DateTime<Local>,
     post_at: DateTime<Local>,
     id: String,
+    /// Seed used for the draw, to re-run it and check the pick was fair.
+    seed: u64,
+    /// ID of the scheduled message this one replaced, for rerolls and swaps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replaces: Option<String>,
+    /// IDs of other messages scheduled around the same day, such as reminders, which don't block the assignment.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unrelated: Vec<String>,
+}
+impl Assignment {
+    fn history_entry(&self, channel: &str) -> HistoryEntry {
+        HistoryEntry {
+            recorded_at: Local::now(),
+            id: self.id.clone(),
+            status: HistoryStatus::Scheduled,
+            channel: channel.to_string(),
+            role: Some(self.role.clone()),
+            members: self.members.clone(),
+            target_date: Some(self.target_date),
+            post_at: self.post_at,
+            seed: Some(self.seed),
+        }
+    }
 }
-impl Display for JokeSuccess {
+impl Display for Assignment {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         formatter.write_fmt(format_args!(
-            "Message '{}' successully scheduled at {}. Schedule ID: {}",
-            self.message, self.post_at, self.id
-        ))
+            "{} assigned as {}. Message '{}' successully scheduled at {}. Schedule ID: {}. Draw seed: {}",
+            self.members.join(", "), self.role, self.message, self.post_at, self.id, self.seed
+        ))?;
+        if let Some(replaced) = &self.replaces {
+            formatter.write_fmt(format_args!(". Replaces: {}", replaced))?;
+        }
+        if !self.unrelated.is_empty() {
+            formatter.write_fmt(format_args!(
+                ". Also scheduled around that day: {}",
+                self.unrelated.join(", ")
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+/// Assignments scheduled for a role, and how many of its slots Slack failed to schedule.
+#[derive(Default)]
+pub struct Filled {
+    pub scheduled: Vec<Assignment>,
+    pub failed: usize,
+}
+
+/// Outcome of cancelling a scheduled message.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelStatus {
+    Cancelled,
+    /// Cancellation wasn't confirmed.
+    Kept,
+    NotFound,
+    Failed,
+}
+
+/// Result of the cancellation of a scheduled message. Printed as JSON with `--output json`.
+#[derive(Serialize)]
+pub struct Cancellation {
+    id: String,
+    status: CancelStatus,
+    /// Slack error, when the deletion failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+impl Display for Cancellation {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            CancelStatus::Cancelled => write!(formatter, "Deleted message with id {}", self.id),
+            CancelStatus::Kept => write!(formatter, "Scheduled message {} kept.", self.id),
+            CancelStatus::NotFound => {
+                write!(formatter, "No scheduled message with id \"{}\"", self.id)
+            }
+            CancelStatus::Failed => write!(
+                formatter,
+                "Failed to delete message with id {}: {}",
+                self.id,
+                self.error.as_deref().unwrap_or("unknown error")
+            ),
+        }
     }
 }
 
@@ -33,13 +165,100 @@ pub struct SlackBot<Api: SlackApiClient> {
     // client: Client,
     config: BotConfig,
     api: Api,
+    prompt: Prompt,
+    /// Where actions on assignments are recorded. Nothing is recorded when unset.
+    history: Option<History>,
 }
 
 impl<Api: SlackApiClient> SlackBot<Api> {
     pub fn new(config: BotConfig, api: Api) -> SlackBot<Api> {
-        SlackBot { config, api }
+        SlackBot {
+            config,
+            api,
+            prompt: Prompt::default(),
+            history: None,
+        }
+    }
+
+    pub fn set_history(&mut self, history: History) {
+        self.history = Some(history);
+    }
+
+    /// Appends to the history, unless it is unset or in a dry run.
+    fn record_history(&self, entries: Vec<HistoryEntry>) {
+        let history = match &self.history {
+            Some(history) if !entries.is_empty() => history,
+            _ => return,
+        };
+        if self.api.is_dry_run() {
+            eprintln!("[dry run] {} history entries not recorded.", entries.len());
+            return;
+        }
+        // Errors are logged, the Slack side is done already anyway.
+        let _ = history.append(&entries);
+    }
+
+    /// History entry of a message being replaced or cancelled, from its last recorded state if any.
+    fn previous_entry(
+        &self,
+        message: &ScheduledMessageObject,
+        role_name: Option<&str>,
+        target_date: Option<DateTime<Local>>,
+        status: HistoryStatus,
+    ) -> HistoryEntry {
+        match self
+            .history
+            .as_ref()
+            .and_then(|history| history.find(&message.id))
+        {
+            Some(entry) => entry.with_status(status),
+            None => HistoryEntry {
+                recorded_at: Local::now(),
+                id: message.id.clone(),
+                status,
+                channel: self.config.channel.clone(),
+                role: role_name.map(String::from),
+                members: message.mentioned_members(),
+                target_date,
+                post_at: message.post_at,
+                seed: None,
+            },
+        }
+    }
+
+    /// Request scheduling an assignment announcement, with metadata telling what it assigns.
+    fn assignment_request(
+        &self,
+        role_name: &str,
+        members: &[String],
+        target_date: &DateTime<Local>,
+        post_at: &DateTime<Local>,
+        seed: u64,
+        text: String,
+    ) -> api::ScheduleMessageRequest {
+        let assignment = AssignmentMetadata {
+            role: role_name.to_string(),
+            target_date: target_date.naive_local().date(),
+            members: members.to_vec(),
+            seed,
+        };
+        api::ScheduleMessageRequest::new(&self.config.channel, post_at.timestamp(), text)
+            .with_metadata(MessageMetadata::from(&assignment))
+    }
+
+    /// Sets how confirmations are answered. Interactive by default.
+    pub fn set_prompt(&mut self, prompt: Prompt) {
+        self.prompt = prompt;
+    }
+    /// Replaces the settings, such as after the config file was edited while running.
+    pub fn set_config(&mut self, config: BotConfig) {
+        self.config = config;
     }
-    pub fn save(self) {
+    pub fn save(&self) {
+        if self.api.is_dry_run() {
+            eprintln!("[dry run] Config file not saved.");
+            return;
+        }
         match self.config.to_file() {
             Ok(_) => info!("Successfully saved config file."),
             Err(_) => error!("Couldnt' save config file"),
@@ -47,6 +266,19 @@ impl<Api: SlackApiClient> SlackBot<Api> {
     }
 
     pub async fn list_scheduled_messages(&self, channel: &str) -> Vec<ScheduledMessageObject> {
+        self.try_list_scheduled_messages(channel)
+            .await
+            .unwrap_or_else(|err| {
+                error!("{:?}", err);
+                Vec::new()
+            })
+    }
+
+    /// All pages of scheduled messages, or the error if any page failed, for callers that can't work on a partial list.
+    async fn try_list_scheduled_messages(
+        &self,
+        channel: &str,
+    ) -> Result<Vec<ScheduledMessageObject>, SlackApiError> {
         let mut request = ScheduledMessagesListRequest {
             channel: Some(channel.to_string()),
             ..ScheduledMessagesListRequest::default()
@@ -64,45 +296,133 @@ impl<Api: SlackApiClient> SlackBot<Api> {
                         .map(ScheduledMessageObject::from);
                     all_responses.extend(page_objects_iterator);
                     debug!("Added to total, {} items", all_responses.len());
-                    if let Some(metadata) = full_response.response_metadata {
-                        if let Some(next_cursor) = metadata.next_cursor {
-                            if !next_cursor.is_empty() {
-                                request.cursor = Some(next_cursor);
-                            } else {
-                                break;
-                            }
-                        } else {
-                            break;
-                        };
+                    match full_response
+                        .response_metadata
+                        .and_then(|metadata| metadata.next_cursor)
+                    {
+                        Some(next_cursor) if !next_cursor.is_empty() => {
+                            request.cursor = Some(next_cursor)
+                        }
+                        _ => break,
                     }
                 }
-                SlackApiContent::Err(err) => {
-                    error!("{:?}", err);
-                    break;
-                }
+                SlackApiContent::Err(err) => return Err(err.error),
             }
         }
 
         debug!("Total {} scheduled message fetched", all_responses.len());
-        all_responses
+        Ok(self.with_known_assignments(all_responses))
     }
 
-    pub async fn joke(
+    /// Adds what each message assigns, for those the history recorded when scheduling them.
+    /// Slack doesn't return the metadata of scheduled messages, so the others are only known from their text.
+    fn with_known_assignments(
         &self,
-        input_date_args: Vec<&str>,
-        scheduled_day_arg: Option<&str>,
-    ) -> Vec<JokeSuccess> {
+        messages: Vec<ScheduledMessageObject>,
+    ) -> Vec<ScheduledMessageObject> {
+        let known = match &self.history {
+            Some(history) => history.current(),
+            None => return messages,
+        };
+        messages
+            .into_iter()
+            .map(|message| {
+                let assignment = known
+                    .iter()
+                    .find(|entry| entry.id == message.id && entry.channel == self.config.channel)
+                    .and_then(HistoryEntry::assignment);
+                match assignment {
+                    Some(assignment) => message.with_assignment(assignment),
+                    None => message,
+                }
+            })
+            .collect()
+    }
+
+    /// Schedules the announcement of who is in charge of the joke.
+    pub async fn joke(&mut self, options: ScheduleOptions<'_>) -> Vec<Assignment> {
         info!("Processing joke command");
-        let target_datetimes: Vec<DateTime<Local>> = self.get_target_dates(input_date_args);
-        debug!("Target dates: {:?}", target_datetimes);
+        self.rota(JOKE_ROLE, options).await
+    }
+
+    /// Schedules the announcement of who is in charge of the role, for each target date.
+    pub async fn rota(&mut self, role_name: &str, options: ScheduleOptions<'_>) -> Vec<Assignment> {
+        self.schedule_rota(role_name, options, None).await.scheduled
+    }
+
+    /// Same as `rota`, also counting the target dates Slack failed to schedule.
+    /// `targets` are used as they are instead of the target dates of the options, when given.
+    async fn schedule_rota(
+        &mut self,
+        role_name: &str,
+        options: ScheduleOptions<'_>,
+        targets: Option<Vec<DateTime<Local>>>,
+    ) -> Filled {
+        info!("Processing rota for role {}", role_name);
+        self.sync_usergroup_members(role_name).await;
+        let role = match self.config.role(role_name) {
+            Some(role) => role.clone(),
+            None => {
+                error!(
+                    "No role named {}. Available roles: {}",
+                    role_name,
+                    self.config.role_names().join(", ")
+                );
+                return Filled::default();
+            }
+        };
+        let mut manual_members: Vec<String> = Vec::new();
+        for input in &options.members {
+            match self.find_member(&role, input).await {
+                Some(member) if !manual_members.contains(&member) => manual_members.push(member),
+                Some(member) => warn!("{} is given several times with --member", member),
+                None => return Filled::default(),
+            }
+        }
+        let members_per_assignment = options
+            .members_per_assignment
+            .unwrap_or(role.members_per_assignment);
+        if !manual_members.is_empty() {
+            if manual_members.len() != members_per_assignment {
+                error!(
+                    "{} assigns {} members per date, got {} with --member.",
+                    role_name,
+                    members_per_assignment,
+                    manual_members.len()
+                );
+                return Filled::default();
+            }
+            info!("Assigning {:?} instead of drawing", manual_members);
+        }
         let already_scheduled_messages = self.list_scheduled_messages(&self.config.channel).await;
+        let target_datetimes: Vec<DateTime<Local>> = if let Some(targets) = targets {
+            targets
+        } else if options.days.is_empty() && (role.schedule.is_some() || options.next.is_some()) {
+            let count = options.next.unwrap_or(1);
+            let slots = self.next_slots(&role, count);
+            let missing =
+                self.missing_assignments(role_name, &role, &slots, &already_scheduled_messages);
+            if missing.is_empty() {
+                info!(
+                    "The next {} slots of {} are already assigned.",
+                    count, role_name
+                );
+            }
+            missing
+        } else {
+            self.get_target_dates(&role, options.days)
+        };
+        debug!("Target dates: {:?}", target_datetimes);
+        if options.seed.is_some() && target_datetimes.len() > 1 {
+            warn!("The same seed will be used for the draw of every date.");
+        }
         let mut messages_to_schedule: Vec<i64> = Vec::new();
-        let mut scheduled = Vec::new();
+        let mut filled = Filled::default();
 
         for target_date in target_datetimes {
             info!("Target datetime: {}.", target_date);
 
-            let post_at = self.get_post_at_date(&target_date, scheduled_day_arg);
+            let post_at = self.get_post_at_date(&role, &target_date, options.post_on);
             info!(
                 "Message schedule datetime: {}. Timestamp {}",
                 post_at,
@@ -116,17 +436,29 @@ impl<Api: SlackApiClient> SlackBot<Api> {
             }
 
             debug!("Checking it isn't already scheduled for channel...");
-            if already_scheduled_messages
-                .iter()
-                .any(|mess| mess.date() == post_at.date())
-            {
-                error!("{} has already been scheduled. Check with `scheduled` command, and/or cancel with the `delete <ID>` command.",
-                    post_at.date()
+            if let Some(conflict) = find_conflict(
+                role_name,
+                &target_date,
+                &post_at,
+                &already_scheduled_messages,
+            ) {
+                error!("The {} assignment of {} has already been scheduled with ID {}. Check with `scheduled` command, and/or cancel with the `cancel <ID>` command.",
+                    role_name,
+                    target_date.naive_local().date(),
+                    conflict.id
                 );
                 continue;
                 // return Err(SlackRError::AlreadyScheduled);
             };
-            debug!("Confirmed nothing already scheduled on {}", post_at);
+            let unrelated: Vec<String> =
+                unrelated_messages_on_day(&target_date, &post_at, &already_scheduled_messages)
+                    .iter()
+                    .map(|message| {
+                        debug!("Also scheduled around {}: {}", target_date, message);
+                        message.id.clone()
+                    })
+                    .collect();
+            debug!("Confirmed nothing already scheduled for {}", target_date);
 
             debug!("Checking it isn't already scheduled in this batch");
             // It is okay to compare timestamps as they both also get the same time assigned.
@@ -140,48 +472,184 @@ impl<Api: SlackApiClient> SlackBot<Api> {
             };
             debug!("Confirmed not duplicating requests");
 
-            let member = match self.select_random_member() {
-                Some(m) => {
-                    info!("Selected member {}", m);
-                    m
-                }
-                None => {
-                    error!("No member could be selected!");
-                    continue;
-                    // return Err(SlackRError::NoMemberToSelect);
+            let seed = options
+                .seed
+                .unwrap_or_else(|| draw_seed(role_name, &target_date, &self.config.channel, false));
+            let mut rng = StdRng::seed_from_u64(seed);
+            // Rotation state as of this date, previous dates of the batch included.
+            let role = self.config.role(role_name).expect("Role exists").clone();
+            let members = if !manual_members.is_empty() {
+                manual_members.clone()
+            } else {
+                info!("Drawing member with seed {}", seed);
+                match select_random_members(&role, &mut rng, &[], members_per_assignment) {
+                    Some(m) => {
+                        info!("Selected members {:?}", m);
+                        m
+                    }
+                    None => {
+                        error!(
+                            "Not enough members to select {} of them!",
+                            members_per_assignment
+                        );
+                        continue;
+                        // return Err(SlackRError::NoMemberToSelect);
+                    }
                 }
             };
 
-            let text = format!(
-                "<@{}> will be in charge of a joke on {}!",
-                member,
-                target_date.naive_local().date()
-            );
+            let text = self
+                .compose_message(
+                    role_name,
+                    &role,
+                    &mut rng,
+                    &members,
+                    &target_date,
+                    &post_at,
+                    false,
+                    seed,
+                )
+                .await;
 
             let request =
-                api::ScheduleMessageRequest::new(&self.config.channel, post_at.timestamp(), text);
+                self.assignment_request(role_name, &members, &target_date, &post_at, seed, text);
             messages_to_schedule.push(request.post_at);
 
-            let response = self.api.schedule_message(&request).await;
-            let success = JokeSuccess {
+            let response = match self.api.schedule_message(&request).await.content {
+                SlackApiContent::Ok(response) => response,
+                SlackApiContent::Err(err) => {
+                    error!(
+                        "Failed to schedule the assignment of {}: {:?}",
+                        target_date.naive_local().date(),
+                        err.error
+                    );
+                    filled.failed += 1;
+                    continue;
+                }
+            };
+            for member in &members {
+                self.record_selection(role_name, member);
+            }
+            let success = Assignment {
+                role: role_name.to_string(),
+                members,
                 message: request.text,
-                target_date: target_date,
+                target_date,
                 post_at: response.post_at,
                 id: response.scheduled_message_id,
+                seed,
+                replaces: None,
+                unrelated,
             };
-            scheduled.push(success);
+            self.record_history(vec![success.history_entry(&self.config.channel)]);
+            filled.scheduled.push(success);
+        }
+        filled
+    }
+
+    /// Schedules the assignments of the role missing among the target days, leaving the ones already scheduled alone.
+    /// Days too late to post for are skipped without error, unlike with `rota`.
+    /// Fails when the scheduled messages can't be listed, as filling from a partial list would schedule duplicates.
+    /// Days Slack fails to schedule are counted in `failed`, to fill them again later.
+    pub async fn fill(
+        &mut self,
+        role_name: &str,
+        days: &[NaiveDate],
+    ) -> Result<Filled, SlackApiError> {
+        let role = match self.config.role(role_name) {
+            Some(role) => role.clone(),
+            None => {
+                error!("No role named {}, nothing to fill.", role_name);
+                return Ok(Filled::default());
+            }
+        };
+        let already_scheduled_messages = self
+            .try_list_scheduled_messages(&self.config.channel)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Couldn't list scheduled messages, not filling role {}: {:?}",
+                    role_name, err
+                );
+                err
+            })?;
+        let targets: Vec<DateTime<Local>> = days
+            .iter()
+            .filter_map(|day| target_datetime(&role, *day))
+            .collect();
+        let missing =
+            self.missing_assignments(role_name, &role, &targets, &already_scheduled_messages);
+        if missing.is_empty() {
+            info!(
+                "Role {} already assigned for the next {} days",
+                role_name,
+                days.len()
+            );
+            return Ok(Filled::default());
         }
-        scheduled
+        info!("Filling role {} for {:?}", role_name, missing);
+        Ok(self
+            .schedule_rota(role_name, ScheduleOptions::default(), Some(missing))
+            .await)
+    }
+
+    /// The next `count` target dates of the role whose announcement can still be posted, assigned or not.
+    fn next_slots(&self, role: &RoleConfig, count: usize) -> Vec<DateTime<Local>> {
+        let now = Local::now();
+        let yesterday = now.date().naive_local().pred();
+        yesterday
+            .iter_days()
+            .skip(1)
+            .filter(|day| role.is_target_day(*day))
+            .filter_map(|day| target_datetime(role, day))
+            .filter(|target_date| self.get_post_at_date(role, target_date, None) > now)
+            .take(count)
+            .collect()
+    }
+
+    /// Target dates without an assignment of the role yet, whose announcement can still be posted.
+    fn missing_assignments(
+        &self,
+        role_name: &str,
+        role: &RoleConfig,
+        targets: &[DateTime<Local>],
+        already_scheduled_messages: &[ScheduledMessageObject],
+    ) -> Vec<DateTime<Local>> {
+        let now = Local::now();
+        targets
+            .iter()
+            .filter(|target_date| {
+                let post_at = self.get_post_at_date(role, target_date, None);
+                post_at > now
+                    && find_conflict(role_name, target_date, &post_at, already_scheduled_messages)
+                        .is_none()
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Dates needing an assignment of the role after `after`, up to `until` included, following its schedule.
+    pub fn target_days(
+        &self,
+        role_name: &str,
+        after: NaiveDate,
+        until: NaiveDate,
+    ) -> Vec<NaiveDate> {
+        self.config
+            .role(role_name)
+            .map(|role| role.target_days(after, until))
+            .unwrap_or_default()
     }
 
     fn get_post_at_date(
         &self,
+        role: &RoleConfig,
         target_date: &DateTime<Local>,
         post_on_day_arg: Option<&str>,
     ) -> DateTime<Local> {
         if let Some(post_on_day) = post_on_day_arg {
             debug!("`post_on_day` was specified");
-            let today_with_post_time = today_with_set_time(self.config.post_time);
+            let today_with_post_time = today_with_set_time(role.post_time);
             let post_at_time =
                 convert_date_string_to_local(post_on_day, &today_with_post_time).unwrap();
             match post_at_time.cmp(target_date) {
@@ -195,7 +663,11 @@ impl<Api: SlackApiClient> SlackBot<Api> {
             }
         };
         debug!("Getting schedule time from target");
-        let unfiltered = *target_date - Duration::days(self.config.advance_days);
+        let unfiltered = *target_date - Duration::days(role.advance_days);
+        // Roles with a schedule including weekends are also announced on weekends.
+        if role.is_target_day(unfiltered.naive_local().date()) {
+            return unfiltered;
+        }
         match unfiltered.date().weekday() {
             Weekday::Sun => {
                 warn!("Offset falling on a sunday, shifting schedule to the Friday before");
@@ -210,15 +682,19 @@ impl<Api: SlackApiClient> SlackBot<Api> {
         }
     }
 
-    fn get_target_dates(&self, input_date_args: Vec<&str>) -> Vec<DateTime<Local>> {
-        let today_with_target_time = today_with_set_time(self.config.target_time);
+    fn get_target_dates(
+        &self,
+        role: &RoleConfig,
+        input_date_args: Vec<&str>,
+    ) -> Vec<DateTime<Local>> {
+        let today_with_target_time = today_with_set_time(role.assignment_time());
         let mut unfiltered_dates = Vec::new();
         if input_date_args.is_empty() {
             debug!("No date was input. Getting tomorrow.");
             let tomorrow = today_with_target_time
                 .date()
                 .succ()
-                .and_time(self.config.target_time)
+                .and_time(role.assignment_time())
                 .unwrap();
             unfiltered_dates.push(tomorrow);
         } else {
@@ -230,6 +706,10 @@ impl<Api: SlackApiClient> SlackBot<Api> {
         }
         debug!("Unfiltered target dates: {:?}", unfiltered_dates);
 
+        if role.schedule.is_some() {
+            // The schedule tells which days need an assignment, weekends included.
+            return unfiltered_dates;
+        }
         let mut all_dates = Vec::new();
         for unfiltered in unfiltered_dates {
             match unfiltered.date().weekday() {
@@ -248,72 +728,508 @@ impl<Api: SlackApiClient> SlackBot<Api> {
         all_dates
     }
 
-    pub async fn reroll(self) {
+    /// Draws new members for a date, allowing to preview the selection to filter out.
+    /// If the assignment is still scheduled, it is replaced in place at its original time, without the previous members.
+    /// Otherwise, a "Reroll" message is posted shortly.
+    /// `excluded` members, as ID, email or name, are never drawn, which allows to reroll without prompts.
+    pub async fn reroll(
+        &mut self,
+        role_name: &str,
+        day: Option<&str>,
+        seed_arg: Option<u64>,
+        excluded: Vec<&str>,
+    ) -> Option<Assignment> {
+        self.sync_usergroup_members(role_name).await;
+        let role = match self.config.role(role_name) {
+            Some(role) => role.clone(),
+            None => {
+                error!("No role named {}", role_name);
+                return None;
+            }
+        };
         let mut exclude = Vec::new();
-        let mut selected_member;
-        let len_limit = self.config.members.len();
+        for input in excluded {
+            match self.find_member(&role, input).await {
+                Some(member) => exclude.push(member),
+                None => {
+                    error!(
+                        "Can't exclude {}, it isn't a member of {}",
+                        input, role_name
+                    );
+                    return None;
+                }
+            }
+        }
+        let target_dates = self.get_target_dates(&role, day.into_iter().collect());
+        let target_date = target_dates.first().unwrap();
+        let messages = self.list_scheduled_messages(&self.config.channel).await;
+        let original = self.find_scheduled_assignment(role_name, &role, target_date, &messages);
+        let previous_members = match original {
+            Some(message) => {
+                info!("Found scheduled assignment: {}", message);
+                message.assigned_members()
+            }
+            None => {
+                warn!(
+                    "No assignment scheduled for {}. A reroll message will be posted instead.",
+                    target_date.naive_local().date()
+                );
+                Vec::new()
+            }
+        };
+        let count = if previous_members.is_empty() {
+            role.members_per_assignment
+        } else {
+            previous_members.len()
+        };
+
+        let seed = seed_arg
+            .unwrap_or_else(|| draw_seed(role_name, target_date, &self.config.channel, true));
+        info!("Drawing member with seed {}", seed);
+        let mut rng = StdRng::seed_from_u64(seed);
+        exclude.extend(previous_members.iter().cloned());
+        let mut selected_members;
         loop {
-            selected_member = match self.select_random_member() {
+            selected_members = match select_random_members(&role, &mut rng, &exclude, count) {
                 Some(m) => {
-                    info!("Selected member {}", m);
+                    info!("Selected members {:?}", m);
                     m
                 }
                 None => {
-                    error!("No member could be selected!");
-                    return;
+                    error!("No member could be selected! You may have excluded all members.");
+                    return None;
                 }
             };
-            if exclude.contains(&selected_member) {
-                info!("Member already excluded. Automatically rerolling");
-                continue;
-            };
-            println!("Member {} was selected. Pick it?", selected_member);
-            if yes() {
+            let question = format!(
+                "Member {} was selected. Pick it?",
+                selected_members.join(", ")
+            );
+            if self.prompt.confirm(&question, true) {
                 break;
             } else {
-                exclude.push(selected_member);
-                if exclude.len() + 1 == len_limit {
-                    error!("You have excluded all members!");
-                    return;
-                };
+                exclude.extend(selected_members);
             };
         }
-        let empty_vec = Vec::new();
-        let target_dates = self.get_target_dates(empty_vec);
-        let target_date = target_dates.first().unwrap();
-        let post_at = Local::now() + Duration::seconds(self.config.instant_delay);
-        let message = format!(
-            "Reroll: <@{}> will be in charge of a joke on {}!",
-            selected_member,
-            target_date.naive_local().date()
+        let (post_at, in_place) = match original {
+            Some(message) => (message.post_at, true),
+            None => (
+                Local::now() + Duration::seconds(self.config.instant_delay),
+                false,
+            ),
+        };
+        // Replacements are the only announcement the channel will see, so they aren't worded as a reroll.
+        let message = self
+            .compose_message(
+                role_name,
+                &role,
+                &mut rng,
+                &selected_members,
+                target_date,
+                &post_at,
+                !in_place,
+                seed,
+            )
+            .await;
+        let request = self.assignment_request(
+            role_name,
+            &selected_members,
+            target_date,
+            &post_at,
+            seed,
+            message,
         );
-        let request =
-            api::ScheduleMessageRequest::new(&self.config.channel, post_at.timestamp(), message);
-        let response = self.api.schedule_message(&request).await;
-        println!("Successfully assigned member {} for a joke on {}. Message will be posted at {}. Schedule ID: {}", 
-            selected_member,
+        let response = match self.api.schedule_message(&request).await.content {
+            SlackApiContent::Ok(response) => response,
+            SlackApiContent::Err(err) => {
+                error!("Failed to schedule the reroll: {:?}", err.error);
+                return None;
+            }
+        };
+        // The replacement is scheduled before deleting the original, so a failure never leaves the date empty.
+        if let Some(original) = original {
+            self.delete_or_warn(&original.id).await;
+        }
+        if let Some(role) = self.config.role_mut(role_name) {
+            role.selected
+                .retain(|member| !previous_members.contains(member));
+        }
+        for member in &selected_members {
+            self.record_selection(role_name, member);
+        }
+        let assignment = Assignment {
+            role: role_name.to_string(),
+            members: selected_members,
+            message: request.text,
+            target_date: *target_date,
+            post_at: response.post_at,
+            id: response.scheduled_message_id,
+            seed,
+            replaces: original.map(|message| message.id.clone()),
+            unrelated: Vec::new(),
+        };
+        let mut entries = Vec::new();
+        if let Some(original) = original {
+            entries.push(self.previous_entry(
+                original,
+                Some(role_name),
+                Some(*target_date),
+                HistoryStatus::Rerolled,
+            ));
+        }
+        entries.push(assignment.history_entry(&self.config.channel));
+        self.record_history(entries);
+        Some(assignment)
+    }
+
+    /// Exchanges the members assigned to two scheduled dates, keeping each original post time.
+    /// Both replacements are scheduled before deleting the originals, and the first one is deleted again if the second fails.
+    pub async fn swap(&self, role_name: &str, day_a: &str, day_b: &str) -> Vec<Assignment> {
+        let role = match self.config.role(role_name) {
+            Some(role) => role,
+            None => {
+                error!("No role named {}", role_name);
+                return Vec::new();
+            }
+        };
+        let target_dates = self.get_target_dates(role, vec![day_a, day_b]);
+        let messages = self.list_scheduled_messages(&self.config.channel).await;
+        let mut originals = Vec::new();
+        for target_date in &target_dates {
+            match self.find_scheduled_assignment(role_name, role, target_date, &messages) {
+                Some(message) => originals.push(message),
+                None => {
+                    error!(
+                        "No assignment scheduled for {}, nothing to swap.",
+                        target_date.naive_local().date()
+                    );
+                    return Vec::new();
+                }
+            }
+        }
+        if originals[0].id == originals[1].id {
+            error!("Both dates refer to the same scheduled message, nothing to swap.");
+            return Vec::new();
+        }
+
+        let mut swapped: Vec<Assignment> = Vec::new();
+        for (index, target_date) in target_dates.iter().enumerate() {
+            let original = originals[index];
+            let members = originals[1 - index].assigned_members();
+            // No draw happens: the seed stays that of the draw which picked the members, when the history knows it.
+            let seed = match originals[1 - index].assignment() {
+                Some(assignment) => assignment.seed,
+                None => draw_seed(role_name, target_date, &self.config.channel, false),
+            };
+            let mut rng = StdRng::seed_from_u64(seed);
+            let text = self
+                .compose_message(
+                    role_name,
+                    role,
+                    &mut rng,
+                    &members,
+                    target_date,
+                    &original.post_at,
+                    false,
+                    seed,
+                )
+                .await;
+            let request = self.assignment_request(
+                role_name,
+                &members,
+                target_date,
+                &original.post_at,
+                seed,
+                text,
+            );
+            match self.api.schedule_message(&request).await.content {
+                SlackApiContent::Ok(response) => swapped.push(Assignment {
+                    role: role_name.to_string(),
+                    members,
+                    message: request.text,
+                    target_date: *target_date,
+                    post_at: response.post_at,
+                    id: response.scheduled_message_id,
+                    seed,
+                    replaces: Some(original.id.clone()),
+                    unrelated: Vec::new(),
+                }),
+                SlackApiContent::Err(err) => {
+                    error!(
+                        "Failed to schedule the swapped assignment of {}: {:?}",
+                        target_date.naive_local().date(),
+                        err.error
+                    );
+                    for assignment in &swapped {
+                        self.delete_or_warn(&assignment.id).await;
+                    }
+                    error!("Swap rolled back, both original assignments are kept.");
+                    return Vec::new();
+                }
+            }
+        }
+        let mut entries = Vec::new();
+        for (index, original) in originals.into_iter().enumerate() {
+            self.delete_or_warn(&original.id).await;
+            entries.push(self.previous_entry(
+                original,
+                Some(role_name),
+                Some(target_dates[index]),
+                HistoryStatus::Rerolled,
+            ));
+            entries.push(swapped[index].history_entry(&self.config.channel));
+        }
+        self.record_history(entries);
+        swapped
+    }
+
+    /// Deletes a scheduled message, telling how to do it by hand if it fails.
+    async fn delete_or_warn(&self, id: &str) {
+        let request = api::DeleteScheduledMessageRequest::new(&self.config.channel, id);
+        if let SlackApiContent::Err(err) = self.api.delete_scheduled_message(&request).await.content
+        {
+            error!(
+                "Failed to delete scheduled message {}: {:?}. Cancel it with `cancel {}`",
+                id, err.error, id
+            );
+        }
+    }
+
+    /// Finds the scheduled message announcing the assignment of the role for the target date, from the history.
+    /// Messages the history doesn't know of, such as those of other operators, are recognized from their posting date and mentions.
+    fn find_scheduled_assignment<'m>(
+        &self,
+        role_name: &str,
+        role: &RoleConfig,
+        target_date: &DateTime<Local>,
+        messages: &'m [ScheduledMessageObject],
+    ) -> Option<&'m ScheduledMessageObject> {
+        let target_day = target_date.naive_local().date();
+        let tagged = messages.iter().find(|message| {
+            message.assignment().is_some_and(|assignment| {
+                assignment.role == role_name && assignment.target_date == target_day
+            })
+        });
+        if tagged.is_some() {
+            return tagged;
+        }
+        let post_at = self.get_post_at_date(role, target_date, None);
+        messages.iter().find(|message| {
+            message.assignment().is_none()
+                && message.date() == post_at.date()
+                && !message.mentioned_members().is_empty()
+        })
+    }
+
+    /// Renders the announcement from one of the role's templates, picked with the draw's RNG.
+    #[allow(clippy::too_many_arguments)]
+    async fn compose_message(
+        &self,
+        role_name: &str,
+        role: &RoleConfig,
+        rng: &mut StdRng,
+        members: &[String],
+        target_date: &DateTime<Local>,
+        post_at: &DateTime<Local>,
+        reroll: bool,
+        seed: u64,
+    ) -> String {
+        let plural = members.len() > 1;
+        // Templates are validated when reading the config, so this only falls back on a config built in memory.
+        let templates = role
+            .message_templates(role_name, self.config.locale, plural)
+            .unwrap_or_else(|err| {
+                error!("{}. Using default template.", err);
+                vec![MessageTemplate::parse(default_template(
+                    self.config.locale,
+                    role_name == JOKE_ROLE,
+                    plural,
+                ))
+                .unwrap()]
+            });
+        let template = templates.choose(rng).expect("At least one template");
+        debug!("Using template {}", template);
+        let names = if template.uses(Placeholder::Name) {
+            let mut names = Vec::new();
+            for member in members {
+                let name = self.get_display_name(member).await;
+                names.push(name.unwrap_or_else(|| member.to_string()));
+            }
+            Some(names)
+        } else {
+            None
+        };
+        let context = MessageContext {
+            members,
+            names,
             target_date,
-            response.post_at,
-            response.scheduled_message_id
+            post_at,
+            reroll,
+            locale: self.config.locale,
+            role: role.label(role_name),
+            cc: role.cc.as_ref().map(UsergroupRef::mention),
+        };
+        let mut text = template.render(&context);
+        if let (Some(cc), false) = (&context.cc, template.uses(Placeholder::Cc)) {
+            text = format!("{} cc {}", text, cc);
+        }
+        if self.config.announce_seed {
+            format!("{} (draw seed: {})", text, seed)
+        } else {
+            text
+        }
+    }
+
+    /// Fetches the display name of a member, falling back to their user name.
+    async fn get_display_name(&self, member: &str) -> Option<String> {
+        self.user_info(member).await.map(|user| user.display_name())
+    }
+
+    async fn user_info(&self, member: &str) -> Option<UserObject> {
+        let request = UserInfoRequest {
+            user: member.to_string(),
+        };
+        let response = self.api.user_info(&request).await;
+        match response.content {
+            SlackApiContent::Ok(response) => Some(response.user),
+            SlackApiContent::Err(err) => {
+                warn!(
+                    "Couldn't get the info of member {}: {:?}",
+                    member, err.error
+                );
+                None
+            }
+        }
+    }
+
+    /// Gets the bot's own member ID from Slack, if not known yet, so it never adds itself as member.
+    async fn ensure_bot_id(&mut self) {
+        if !self.config.id.is_empty() {
+            return;
+        }
+        match self.api.auth_test().await.content {
+            SlackApiContent::Ok(identity) => {
+                info!("Bot member ID is {}", identity.user_id);
+                self.config.id = identity.user_id;
+            }
+            SlackApiContent::Err(err) => {
+                warn!("Couldn't get the bot's own ID: {:?}", err.error)
+            }
+        }
+    }
+
+    /// Checks the accounts to import as members, leaving out those the filter excludes.
+    /// Accounts whose info can't be fetched are imported, with a warning.
+    async fn screen_imports(
+        &self,
+        candidates: Vec<String>,
+        filter: ImportFilter,
+    ) -> (Vec<MemberChange>, Vec<Exclusion>) {
+        let mut imported = Vec::new();
+        let mut excluded = Vec::new();
+        for id in candidates {
+            let user = match self.user_info(&id).await {
+                Some(user) => user,
+                None => {
+                    warn!("Importing {} without checking its account", id);
+                    imported.push(MemberChange { id, name: None });
+                    continue;
+                }
+            };
+            match filter.exclusion_reason(&user) {
+                Some(reason) => {
+                    info!("Not importing {}: {:?}", id, reason);
+                    excluded.push(Exclusion {
+                        id,
+                        name: user.display_name(),
+                        reason,
+                    });
+                }
+                None => imported.push(MemberChange {
+                    id,
+                    name: Some(user.display_name()),
+                }),
+            }
+        }
+        (imported, excluded)
+    }
+
+    /// Finds the ID of a member of the role, given the same way as to `find_user`.
+    async fn find_member(&self, role: &RoleConfig, input: &str) -> Option<String> {
+        if role.members.iter().any(|member| member == input) {
+            return Some(input.to_string());
+        }
+        let user = self.find_user(input).await?;
+        if role.members.contains(&user.id) {
+            Some(user.id)
+        } else {
+            error!(
+                "{} ({}) isn't a member of the role. Add them to the config first.",
+                input, user.id
+            );
+            None
+        }
+    }
+
+    fn record_selection(&mut self, role_name: &str, member: &str) {
+        if let Some(role) = self.config.role_mut(role_name) {
+            role.record_selection(member);
+            debug!("Rotation of {} now at {:?}", role_name, role.selected);
+        }
+    }
+
+    /// Gets the role to edit, logging an error if it doesn't exist.
+    fn edit_role(&mut self, role_name: &str) -> Option<&mut RoleConfig> {
+        if self.config.role(role_name).is_none() {
+            error!(
+                "No role named {}. Add it first with `add role {}`",
+                role_name, role_name
+            );
+        }
+        self.config.role_mut(role_name)
+    }
+
+    /// Adds a new role, starting with the same members as the joke.
+    pub fn add_role(&mut self, role_name: &str, label: Option<&str>) {
+        if self.config.role(role_name).is_some() {
+            error!("Role {} already exists", role_name);
+            return;
+        }
+        let role = RoleConfig {
+            members: self.config.joke.members.clone(),
+            label: label.map(|label| label.to_string()),
+            ..RoleConfig::default()
+        };
+        info!(
+            "Adding role {} with {} members",
+            role_name,
+            role.members.len()
         );
+        self.config.roles.insert(role_name.to_string(), role);
     }
 
-    fn select_random_member(&self) -> Option<String> {
-        debug!("Selecting member");
-        let mut rng = rand::thread_rng();
-        self.config
-            .members
-            .choose(&mut rng)
-            .map(|member| member.to_owned())
+    pub fn set_locale(&mut self, locale: &str) {
+        self.config.locale = locale.parse::<Locale>().expect("Locale was validated");
+    }
+
+    pub fn add_templates(&mut self, role_name: &str, templates: Vec<&str>, replace: bool) {
+        if let Some(role) = self.edit_role(role_name) {
+            if replace {
+                info!("Replacing {} templates", role.templates.len());
+                role.templates.clear();
+            }
+            role.templates
+                .extend(templates.into_iter().map(|template| template.to_string()));
+        }
     }
 
+    /// Returns the import summary of the given members, and the accounts of the channel left out by the filter, when a channel is given.
     pub async fn config(
-        mut self,
+        &mut self,
         members_opt: Option<Vec<String>>,
         channel_opt: Option<&str>,
         token_opt: Option<&str>,
         target_time_opt: Option<&str>,
-    ) {
+    ) -> (Option<ImportSummary>, Vec<Exclusion>) {
         info!("Processing config command");
         // let mut build_config = self.config;
         // debug!("Parsing given config arguments");
@@ -321,67 +1237,419 @@ impl<Api: SlackApiClient> SlackBot<Api> {
         //     info!("Token: {}", token);
         //     self.add_token(token).await;
         // };
+        let mut summary = None;
         if let Some(members) = members_opt {
             debug!("Got members {:?}", members);
-            for email in members {
-                self.add_member_from_email(&email).await;
-            }
+            summary = self.import_members(JOKE_ROLE, members).await;
         };
+        let mut excluded = Vec::new();
         if let Some(channel) = channel_opt {
             info!("Channel: {}", channel);
-            self.add_channel(channel).await;
+            excluded = self.add_channel(channel, ImportFilter::default()).await;
         };
 
         if let Some(target_time) = target_time_opt {
             info!("Target time: {}", target_time);
-            self.add_target_time(target_time);
+            self.add_target_time(JOKE_ROLE, target_time);
         };
-        println!("{:?}", self.config);
+        (summary, excluded)
+    }
+
+    pub fn settings(&self) -> &BotConfig {
+        &self.config
+    }
+
+    /// Saves the config file once confirmed.
+    pub fn save_with_confirmation(self) {
         let path = BotConfig::get_config_path();
-        println!("Save to file at {:?}? y/n", path);
-        if yes() {
+        if self
+            .prompt
+            .confirm(&format!("Save to file at {:?}?", path), false)
+        {
             self.save();
         }
     }
 
-    /// Takes a user email as input, fetches its ID and adds its ID to the config members (with confirmation for matching user)
-    pub async fn add_member_from_email(&mut self, email: &str) {
+    /// Adds a member to the role once confirmed, found from their Slack ID, email, @handle or name.
+    /// When several users of the workspace have that name, asks which one. Returns the added ID.
+    pub async fn add_member(&mut self, role_name: &str, input: &str) -> Option<String> {
         info!("Processing add member command");
+        if self.config.role(role_name).is_none() {
+            error!("No role named {}", role_name);
+            return None;
+        }
+        let user = self.find_user(input).await?;
+        let role = self.config.role_mut(role_name).expect("Role exists");
+        if role.members.contains(&user.id) {
+            warn!("{} is already a member of {}", user.id, role_name);
+            return None;
+        }
+        let question = format!(
+            "Found user {}. Is it who you want, save its ID {} in config?",
+            user.display_name(),
+            user.id
+        );
+        if self.prompt.confirm(&question, false) {
+            let role = self.config.role_mut(role_name).expect("Role exists");
+            role.members.push(user.id.clone());
+            Some(user.id)
+        } else {
+            None
+        }
+    }
+
+    /// Finds a user of the workspace from their Slack ID, email, or @handle, display name or real name.
+    async fn find_user(&self, input: &str) -> Option<UserObject> {
+        if is_user_id(input) {
+            if let Some(user) = self.user_info(input).await {
+                return Some(user);
+            }
+            debug!("{} isn't a known user ID, looking it up as a name", input);
+        } else if is_email(input) {
+            return self.user_by_email(input).await;
+        }
+        let users = self.directory().await?;
+        let mut matches: Vec<UserObject> = users
+            .into_iter()
+            .filter(|user| !user.deleted && user.has_name(input))
+            .collect();
+        match matches.len() {
+            0 => {
+                error!("No user of the workspace is named {}", input);
+                None
+            }
+            1 => matches.pop(),
+            _ => {
+                let options: Vec<String> = matches
+                    .iter()
+                    .map(|user| format!("{} (@{}, {})", user.display_name(), user.name, user.id))
+                    .collect();
+                let question = format!("Several users are named {}, which one?", input);
+                match self.prompt.choose(&question, &options) {
+                    Some(choice) => Some(matches.swap_remove(choice)),
+                    None => {
+                        error!(
+                            "No user picked for {}. Use their ID or email instead.",
+                            input
+                        );
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /// Gets a user from their email, logging why when it fails.
+    async fn user_by_email(&self, email: &str) -> Option<UserObject> {
         let request = api::UserLookupRequest {
             email: email.to_string(),
         };
-        let response = self.api.user_lookup_by_email(&request).await;
-        match response.content {
-            SlackApiContent::Ok(response) => {
-                let name = response
-                    .user
-                    .profile
-                    .display_name
-                    .or(Some(response.user.name))
-                    .unwrap();
-                println!(
-                    "Found user {}. Is it who you want, save its ID {} in config? y/n",
-                    name, response.user.id
-                );
-                if yes() {
-                    self.config.members.push(response.user.id);
-                }
-            }
+        match self.api.user_lookup_by_email(&request).await.content {
+            SlackApiContent::Ok(response) => Some(response.user),
             SlackApiContent::Err(slack_err) => {
                 match slack_err.error {
                     SlackApiError::users_not_found => {
-                        error!("User email was not found, or the bot doesn't have access to it.")
+                        error!(
+                            "User email {} was not found, or the bot doesn't have access to it.",
+                            email
+                        )
                     }
                     SlackApiError::missing_scope => {
                         error!("Usage of lookup by email requires the Slack `users:read.email` scope. Please verify bot permissions.")
                     }
                     _ => error!("{:?}", slack_err.error),
                 };
+                None
+            }
+        }
+    }
+
+    /// Every account of the workspace, logging why when they can't be listed.
+    async fn directory(&self) -> Option<Vec<UserObject>> {
+        match self.list_users().await {
+            Ok(users) => Some(users),
+            Err(SlackApiError::missing_scope) => {
+                error!("Looking up users by ID or name requires the Slack `users:read` scope. Please verify bot permissions.");
+                None
+            }
+            Err(err) => {
+                error!("Couldn't list the users of the workspace: {:?}", err);
+                None
+            }
+        }
+    }
+
+    /// Adds members to the role in bulk from their emails or Slack IDs, once the summary is confirmed.
+    /// IDs are found in a single listing of the workspace. Emails are looked up concurrently, `LOOKUP_CONCURRENCY` at a time.
+    pub async fn import_members(
+        &mut self,
+        role_name: &str,
+        inputs: Vec<String>,
+    ) -> Option<ImportSummary> {
+        let role_members = match self.config.role(role_name) {
+            Some(role) => role.members.clone(),
+            None => {
+                error!("No role named {}", role_name);
+                return None;
+            }
+        };
+        let mut unique: Vec<String> = Vec::new();
+        for input in inputs {
+            if !unique.contains(&input) {
+                unique.push(input);
+            }
+        }
+        info!("Looking up {} members", unique.len());
+        let mut users = if unique.iter().any(|input| !is_email(input)) {
+            self.directory().await.unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        let mut lookups: Vec<(usize, String, Option<UserObject>)> = Vec::new();
+        let mut emails = Vec::new();
+        for (index, input) in unique.into_iter().enumerate() {
+            if is_email(&input) {
+                emails.push((index, input));
+            } else {
+                let user = users
+                    .iter()
+                    .position(|user| user.id == input)
+                    .map(|position| users.swap_remove(position));
+                lookups.push((index, input, user));
+            }
+        }
+        let bot = &*self;
+        let email_lookups: Vec<(usize, String, Option<UserObject>)> = stream::iter(emails)
+            .map(|(index, input)| async move {
+                let user = bot.user_by_email(&input).await;
+                (index, input, user)
+            })
+            .buffer_unordered(LOOKUP_CONCURRENCY)
+            .collect()
+            .await;
+        lookups.extend(email_lookups);
+        lookups.sort_by_key(|(index, _input, _user)| *index);
+
+        let mut summary = ImportSummary {
+            role: role_name.to_string(),
+            ..ImportSummary::default()
+        };
+        for (_index, input, user) in lookups {
+            let user = match user {
+                Some(user) => user,
+                None => {
+                    summary.not_found.push(input);
+                    continue;
+                }
+            };
+            let member = MemberChange {
+                name: Some(user.display_name()),
+                id: user.id,
+            };
+            if role_members.contains(&member.id) {
+                summary.already_present.push(member);
+            } else if !summary.found.iter().any(|found| found.id == member.id) {
+                // Given both by email and ID.
+                summary.found.push(member);
+            }
+        }
+        if summary.found.is_empty() {
+            return Some(summary);
+        }
+        let question = format!(
+            "{}\nAdd {} members to {}?",
+            summary.summary(),
+            summary.found.len(),
+            role_name
+        );
+        if self.prompt.confirm(&question, false) {
+            let role = self.config.role_mut(role_name).expect("Role exists");
+            role.members
+                .extend(summary.found.iter().map(|member| member.id.clone()));
+            info!("Added {} members to {}", summary.found.len(), role_name);
+            summary.applied = true;
+        }
+        Some(summary)
+    }
+
+    /// IDs of the members of a user group, given as @handle or ID.
+    pub async fn usergroup_users(&self, usergroup: &str) -> Option<Vec<String>> {
+        let group = self.find_usergroup(usergroup).await?;
+        self.usergroup_member_ids(&group).await
+    }
+
+    /// Finds a user group of the workspace from its @handle or ID.
+    async fn find_usergroup(&self, usergroup: &str) -> Option<UsergroupRef> {
+        let request = UsergroupsListRequest {
+            include_disabled: None,
+        };
+        let usergroups = match self.api.list_usergroups(&request).await.content {
+            SlackApiContent::Ok(response) => response.usergroups,
+            SlackApiContent::Err(err) => {
+                match err.error {
+                    SlackApiError::missing_scope => error!("Listing user groups requires the Slack `usergroups:read` scope. Please verify bot permissions."),
+                    error => error!("Couldn't list the user groups: {:?}", error),
+                }
+                return None;
+            }
+        };
+        match usergroups.into_iter().find(|group| group.is(usergroup)) {
+            Some(group) => Some(UsergroupRef {
+                id: group.id,
+                handle: group.handle,
+            }),
+            None => {
+                error!("No user group {} in the workspace", usergroup);
+                None
+            }
+        }
+    }
+
+    async fn usergroup_member_ids(&self, group: &UsergroupRef) -> Option<Vec<String>> {
+        let request = UsergroupUsersListRequest {
+            usergroup: group.id.clone(),
+        };
+        match self.api.list_usergroup_users(&request).await.content {
+            SlackApiContent::Ok(response) => {
+                info!(
+                    "User group @{} has {} members",
+                    group.handle,
+                    response.users.len()
+                );
+                Some(response.users)
+            }
+            SlackApiContent::Err(err) => {
+                error!(
+                    "Couldn't list the members of user group @{}: {:?}",
+                    group.handle, err.error
+                );
+                None
+            }
+        }
+    }
+
+    /// Makes the members of the role those of its user group, if it has one, leaving out the accounts the default filter excludes.
+    /// Members who left the group also leave the rotation. On failure, the current members are kept.
+    /// Returns the accounts of the group left out, each also logged as a warning.
+    async fn sync_usergroup_members(&mut self, role_name: &str) -> Vec<Exclusion> {
+        let group = match self
+            .config
+            .role(role_name)
+            .and_then(|role| role.member_usergroup.clone())
+        {
+            Some(group) => group,
+            None => return Vec::new(),
+        };
+        let mut candidates = match self.usergroup_member_ids(&group).await {
+            Some(members) => members,
+            None => {
+                warn!(
+                    "Keeping the current members of {}, couldn't sync them from @{}",
+                    role_name, group.handle
+                );
+                return Vec::new();
+            }
+        };
+        let bot_id = self.config.id.clone();
+        candidates.retain(|member| member != &bot_id);
+        let (imported, excluded) = self
+            .screen_imports(candidates, ImportFilter::default())
+            .await;
+        for exclusion in &excluded {
+            warn!("{} from @{}", exclusion, group.handle);
+        }
+        let members: Vec<String> = imported.into_iter().map(|member| member.id).collect();
+        let role = self.config.role_mut(role_name).expect("Role exists");
+        let added = members.iter().filter(|m| !role.members.contains(m)).count();
+        let removed = role.members.iter().filter(|m| !members.contains(m)).count();
+        if added > 0 || removed > 0 {
+            info!(
+                "Synced {} from @{}: {} members added, {} removed",
+                role_name, group.handle, added, removed
+            );
+        }
+        role.selected.retain(|member| members.contains(member));
+        role.disabled.retain(|member| members.contains(member));
+        role.members = members;
+        excluded
+    }
+
+    /// Sets the user group mentioned in the announcements of the role, given as @handle or ID. `none` removes it.
+    pub async fn set_cc(&mut self, role_name: &str, usergroup: &str) {
+        if self.config.role(role_name).is_none() {
+            error!("No role named {}", role_name);
+            return;
+        }
+        let cc = if usergroup.eq_ignore_ascii_case("none") {
+            None
+        } else {
+            match self.find_usergroup(usergroup).await {
+                Some(group) => Some(group),
+                None => return,
+            }
+        };
+        if let Some(role) = self.edit_role(role_name) {
+            info!("Cc of {} set to {:?}", role_name, cc);
+            role.cc = cc;
+        }
+    }
+
+    /// Syncs the members of the role from a user group, given as @handle or ID, before each draw. `none` stops syncing.
+    /// Members are synced right away, so the change shows in the config. Returns the accounts of the group left out.
+    pub async fn set_member_usergroup(
+        &mut self,
+        role_name: &str,
+        usergroup: &str,
+    ) -> Vec<Exclusion> {
+        if self.config.role(role_name).is_none() {
+            error!("No role named {}", role_name);
+            return Vec::new();
+        }
+        let group = if usergroup.eq_ignore_ascii_case("none") {
+            None
+        } else {
+            match self.find_usergroup(usergroup).await {
+                Some(group) => Some(group),
+                None => return Vec::new(),
             }
+        };
+        if let Some(role) = self.edit_role(role_name) {
+            info!("Members of {} synced from {:?}", role_name, group);
+            role.member_usergroup = group;
         }
+        self.sync_usergroup_members(role_name).await
     }
 
-    pub async fn add_channel(&mut self, channel: &str) {
+    /// Every account of the workspace, going through all the pages.
+    async fn list_users(&self) -> Result<Vec<UserObject>, SlackApiError> {
+        let mut users = Vec::new();
+        let mut request = UsersListRequest {
+            cursor: None,
+            limit: Some(200),
+        };
+        loop {
+            let full_response = self.api.list_users(&request).await;
+            match full_response.content {
+                SlackApiContent::Ok(response) => {
+                    users.extend(response.members);
+                    match full_response
+                        .response_metadata
+                        .and_then(|metadata| metadata.next_cursor)
+                    {
+                        Some(next_cursor) if !next_cursor.is_empty() => {
+                            request.cursor = Some(next_cursor)
+                        }
+                        _ => break,
+                    }
+                }
+                SlackApiContent::Err(err) => return Err(err.error),
+            }
+        }
+        Ok(users)
+    }
+
+    /// Returns the accounts of the channel left out by the filter.
+    pub async fn add_channel(&mut self, channel: &str, filter: ImportFilter) -> Vec<Exclusion> {
         // shadowing to string
         let channel = channel.to_string();
 
@@ -402,9 +1670,10 @@ impl<Api: SlackApiClient> SlackBot<Api> {
             },
             SlackApiContent::Err(err) => {
                 error!("Couldn't join channel. Error: {:?}. Aborting.", err.error);
-                return;
+                return Vec::new();
             }
         };
+        self.ensure_bot_id().await;
         let members = match self.list_members_for_channel(&channel).await {
             Ok(m) => m,
             Err(err) => {
@@ -418,18 +1687,23 @@ impl<Api: SlackApiClient> SlackBot<Api> {
                 vec![]
             }
         };
-        self.config.members.extend(
-            members
-                .into_iter()
-                .filter(|e| e != &self.config.id)
-                .filter(|e| !self.config.members.contains(e))
-                .collect::<Vec<String>>(),
-        );
+        let new_members = members
+            .into_iter()
+            .filter(|e| e != &self.config.id)
+            .filter(|e| !self.config.joke.members.contains(e))
+            .collect::<Vec<String>>();
+        let (imported, excluded) = self.screen_imports(new_members, filter).await;
+        info!("Adding {} members", imported.len());
+        self.config
+            .joke
+            .members
+            .extend(imported.into_iter().map(|member| member.id));
         // self.config.members = self.config.members.into_iter()
         //                                         .chain(members)
         //                                         .collect();
 
         self.config.channel = channel;
+        excluded
     }
 
     async fn list_members_for_channel(&self, channel: &str) -> Result<Vec<String>, SlackApiError> {
@@ -444,16 +1718,14 @@ impl<Api: SlackApiClient> SlackBot<Api> {
             match full_response.content {
                 SlackApiContent::Ok(response) => {
                     members.extend(response.members);
-                    if let Some(metadata) = full_response.response_metadata {
-                        if let Some(next_cursor) = metadata.next_cursor {
-                            if !next_cursor.is_empty() {
-                                request.cursor = Some(next_cursor);
-                            } else {
-                                break;
-                            }
-                        } else {
-                            break;
-                        };
+                    match full_response
+                        .response_metadata
+                        .and_then(|metadata| metadata.next_cursor)
+                    {
+                        Some(next_cursor) if !next_cursor.is_empty() => {
+                            request.cursor = Some(next_cursor)
+                        }
+                        _ => break,
                     }
                 }
                 SlackApiContent::Err(err) => return Err(err.error),
@@ -462,6 +1734,202 @@ impl<Api: SlackApiClient> SlackBot<Api> {
         Ok(members)
     }
 
+    /// Compares the members of the role with those of the channel, and applies the difference once confirmed.
+    /// New channel members are added to the role. Departed ones are removed from every role, and from their rotation.
+    /// New members are checked against the filter like with `add_channel`.
+    pub async fn sync_members(
+        &mut self,
+        role_name: &str,
+        filter: ImportFilter,
+    ) -> Option<MembersDiff> {
+        let role_members = match self.config.role(role_name) {
+            Some(role) => role.members.clone(),
+            None => {
+                error!("No role named {}", role_name);
+                return None;
+            }
+        };
+        if self.config.channel.is_empty() {
+            error!("No channel set yet. Set it with `add channel <channel>` first.");
+            return None;
+        }
+        self.ensure_bot_id().await;
+        let channel_members = match self.list_members_for_channel(&self.config.channel).await {
+            Ok(members) => members,
+            Err(err) => {
+                error!(
+                    "Couldn't list the members of channel {}: {:?}",
+                    self.config.channel, err
+                );
+                return None;
+            }
+        };
+        let mut diff = MembersDiff::compute(
+            role_name,
+            &role_members,
+            &self.all_members(),
+            &channel_members,
+            &self.config.id,
+        );
+        let candidates = diff.added.drain(..).map(|member| member.id).collect();
+        let (imported, excluded) = self.screen_imports(candidates, filter).await;
+        diff.added = imported;
+        diff.excluded = excluded;
+        if diff.is_empty() {
+            return Some(diff);
+        }
+        for member in diff.removed.iter_mut() {
+            member.name = self.get_display_name(&member.id).await;
+        }
+        let question = format!(
+            "{}\nAdd {} members to {}, and remove {} members from every role?",
+            diff.summary(),
+            diff.added.len(),
+            role_name,
+            diff.removed.len()
+        );
+        if !self.prompt.confirm(&question, false) {
+            return Some(diff);
+        }
+        let removed: Vec<String> = diff.removed.iter().map(|m| m.id.clone()).collect();
+        let role_names: Vec<String> = self
+            .config
+            .role_names()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        for name in role_names {
+            let role = self.config.role_mut(&name).expect("Listed role");
+            role.members.retain(|member| !removed.contains(member));
+            role.selected.retain(|member| !removed.contains(member));
+            role.disabled.retain(|member| !removed.contains(member));
+        }
+        let role = self.config.role_mut(role_name).expect("Role exists");
+        role.members
+            .extend(diff.added.iter().map(|member| member.id.clone()));
+        info!(
+            "Added {} members to {}, removed {} members",
+            diff.added.len(),
+            role_name,
+            removed.len()
+        );
+        diff.applied = true;
+        Some(diff)
+    }
+
+    /// Members of the role with their display name, and whether they are disabled or had their turn.
+    pub async fn list_members(&self, role_name: &str) -> Vec<MemberInfo> {
+        let role = match self.config.role(role_name) {
+            Some(role) => role,
+            None => {
+                error!("No role named {}", role_name);
+                return Vec::new();
+            }
+        };
+        let mut members = Vec::new();
+        for member in &role.members {
+            members.push(MemberInfo {
+                id: member.clone(),
+                name: self.get_display_name(member).await,
+                disabled: role.disabled.contains(member),
+                selected: role.selected.contains(member),
+            });
+        }
+        members
+    }
+
+    /// Removes members from the role and its rotation. Their past assignments stay in the history.
+    /// Members are given as to `find_user`.
+    pub async fn remove_members(
+        &mut self,
+        role_name: &str,
+        inputs: Vec<&str>,
+    ) -> Vec<MemberUpdate> {
+        let mut updates = Vec::new();
+        for member in self.resolve_members(role_name, inputs).await {
+            let role = self.config.role_mut(role_name).expect("Role exists");
+            role.members.retain(|m| m != &member);
+            role.selected.retain(|m| m != &member);
+            role.disabled.retain(|m| m != &member);
+            updates.push(
+                self.member_update(role_name, member, MemberAction::Removed)
+                    .await,
+            );
+        }
+        updates
+    }
+
+    /// Takes members out of the draw of the role, or back in, without losing their place in the rotation.
+    /// Members are given as to `find_user`.
+    pub async fn set_members_disabled(
+        &mut self,
+        role_name: &str,
+        inputs: Vec<&str>,
+        disabled: bool,
+    ) -> Vec<MemberUpdate> {
+        let mut updates = Vec::new();
+        for member in self.resolve_members(role_name, inputs).await {
+            let role = self.config.role_mut(role_name).expect("Role exists");
+            let action = if disabled {
+                if !role.disabled.contains(&member) {
+                    role.disabled.push(member.clone());
+                }
+                MemberAction::Disabled
+            } else {
+                role.disabled.retain(|m| m != &member);
+                MemberAction::Enabled
+            };
+            updates.push(self.member_update(role_name, member, action).await);
+        }
+        updates
+    }
+
+    /// IDs of the members of the role given as to `find_user`. Unknown ones are logged and skipped.
+    async fn resolve_members(&self, role_name: &str, inputs: Vec<&str>) -> Vec<String> {
+        let role = match self.config.role(role_name) {
+            Some(role) => role,
+            None => {
+                error!("No role named {}", role_name);
+                return Vec::new();
+            }
+        };
+        let mut members = Vec::new();
+        for input in inputs {
+            if let Some(member) = self.find_member(role, input).await {
+                members.push(member);
+            }
+        }
+        members
+    }
+
+    async fn member_update(
+        &self,
+        role_name: &str,
+        member: String,
+        action: MemberAction,
+    ) -> MemberUpdate {
+        info!("{:?} {} in {}", action, member, role_name);
+        MemberUpdate {
+            role: role_name.to_string(),
+            name: self.get_display_name(&member).await,
+            id: member,
+            action,
+        }
+    }
+
+    /// Members of every role, each once.
+    fn all_members(&self) -> Vec<String> {
+        let mut all: Vec<String> = Vec::new();
+        for name in self.config.role_names() {
+            for member in &self.config.role(name).expect("Listed role").members {
+                if !all.contains(member) {
+                    all.push(member.clone());
+                }
+            }
+        }
+        all
+    }
+
     // pub async fn add_token(&mut self, token: &str) {
     //     let new_client = client::create_client(token.to_string());
     //     let request = api::Empty {};
@@ -476,66 +1944,335 @@ impl<Api: SlackApiClient> SlackBot<Api> {
     //     }
     // }
 
-    pub fn add_target_time(&mut self, target_time: &str) {
-        self.config.target_time = NaiveTime::parse_from_str(target_time, "%H:%M:%S")
-            .expect("Unable to parse target time");
+    pub fn add_target_time(&mut self, role_name: &str, target_time: &str) {
+        if let Some(role) = self.edit_role(role_name) {
+            role.target_time = NaiveTime::parse_from_str(target_time, "%H:%M:%S")
+                .expect("Unable to parse target time");
+            if let Some(schedule) = &role.schedule {
+                warn!(
+                    "Role {} has a schedule, {}, whose time is used instead of the target time",
+                    role_name, schedule
+                );
+            }
+        }
     }
 
-    pub fn add_post_time(&mut self, offset: &str) {
-        self.config.post_time =
-            NaiveTime::parse_from_str(offset, "%H:%M:%S").expect("Unable to parse offset time");
+    /// Sets the days and time needing an assignment, or removes the schedule with "none".
+    pub fn set_schedule(&mut self, role_name: &str, schedule: &str) {
+        if let Some(role) = self.edit_role(role_name) {
+            role.schedule = if schedule.eq_ignore_ascii_case("none") {
+                None
+            } else {
+                Some(schedule.parse().expect("Schedule was validated"))
+            };
+            info!("Schedule of {} set to {:?}", role_name, role.schedule);
+        }
     }
 
-    pub fn set_post_day_offset(&mut self, offset: &str) {
-        self.config.advance_days = offset
-            .parse::<i64>()
-            .expect("Day offset not parsable to i64");
+    pub fn add_post_time(&mut self, role_name: &str, offset: &str) {
+        if let Some(role) = self.edit_role(role_name) {
+            role.post_time =
+                NaiveTime::parse_from_str(offset, "%H:%M:%S").expect("Unable to parse offset time");
+        }
     }
 
-    pub async fn check_scheduled_messages(self) {
+    pub fn set_post_day_offset(&mut self, role_name: &str, offset: &str) {
+        if let Some(role) = self.edit_role(role_name) {
+            role.advance_days = offset
+                .parse::<i64>()
+                .expect("Day offset not parsable to i64");
+        }
+    }
+
+    /// Current state of each recorded assignment, oldest first.
+    pub fn history(&self) -> Vec<HistoryEntry> {
+        self.history
+            .as_ref()
+            .map(History::current)
+            .unwrap_or_default()
+    }
+
+    /// Assignment counts per member over the period, from the history and the messages still scheduled on Slack.
+    /// Without a role, counts all roles together, and includes scheduled messages the history doesn't know of.
+    pub async fn stats(
+        &self,
+        role_name: Option<&str>,
+        since: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+    ) -> Option<Stats> {
+        let members: Vec<String> = match role_name {
+            Some(name) => match self.config.role(name) {
+                Some(role) => role.members.clone(),
+                None => {
+                    error!("No role named {}", name);
+                    return None;
+                }
+            },
+            None => {
+                let mut all = Vec::new();
+                for name in self.config.role_names() {
+                    for member in &self.config.role(name).expect("Role exists").members {
+                        if !all.contains(member) {
+                            all.push(member.clone());
+                        }
+                    }
+                }
+                all
+            }
+        };
+        let entries = self.known_assignments().await;
+        Some(Stats::compute(&entries, &members, role_name, since, until))
+    }
+
+    /// Current state of the assignments in the history, with the messages still scheduled on Slack the history doesn't know of.
+    async fn known_assignments(&self) -> Vec<HistoryEntry> {
+        let mut entries = self.history();
+        for message in self.list_scheduled_messages(&self.config.channel).await {
+            if !entries.iter().any(|entry| entry.id == message.id) {
+                debug!("Scheduled message {} is not in the history", message.id);
+                entries.push(self.previous_entry(&message, None, None, HistoryStatus::Scheduled));
+            }
+        }
+        entries
+    }
+
+    /// Renders the past and upcoming assignments, by target date, with the display names of the members.
+    pub async fn export(&self, format: ExportFormat) -> String {
+        let mut entries = self.known_assignments().await;
+        entries.sort_by_key(|entry| entry.target_date.unwrap_or(entry.post_at));
+        let mut names = HashMap::new();
+        for entry in &entries {
+            for member in &entry.members {
+                if !names.contains_key(member) {
+                    let name = self
+                        .get_display_name(member)
+                        .await
+                        .unwrap_or_else(|| member.clone());
+                    names.insert(member.clone(), name);
+                }
+            }
+        }
+        let labels: HashMap<String, String> = self
+            .config
+            .role_names()
+            .into_iter()
+            .map(|name| {
+                let role = self.config.role(name).expect("Role exists");
+                (name.to_string(), role.label(name).to_string())
+            })
+            .collect();
+        format.render(&entries, &names, &labels)
+    }
+
+    /// Reconciles the history and rotation state with the messages Slack actually has scheduled, when several people run the bot.
+    /// Messages deleted elsewhere are cancelled, and their members get their turn back.
+    /// Assignments scheduled by other operators are added to the history and the rotation.
+    /// They are attributed to the joke role when it is the only role, their role is unknown otherwise.
+    pub async fn sync(&mut self) -> Vec<SyncChange> {
+        let messages = match self.try_list_scheduled_messages(&self.config.channel).await {
+            Ok(messages) => messages,
+            Err(err) => {
+                error!(
+                    "Couldn't list scheduled messages, nothing synced: {:?}",
+                    err
+                );
+                return Vec::new();
+            }
+        };
+        let known: Vec<HistoryEntry> = self
+            .history()
+            .into_iter()
+            .filter(|entry| entry.channel == self.config.channel)
+            .collect();
+        let default_role = if self.config.roles.is_empty() {
+            Some(JOKE_ROLE)
+        } else {
+            None
+        };
+        let changes = reconcile(&known, &messages, Local::now(), |message| {
+            self.previous_entry(message, default_role, None, HistoryStatus::Scheduled)
+        });
+        for change in &changes {
+            let role_name = match change.entry.role.as_deref() {
+                Some(role_name) => role_name,
+                None => continue,
+            };
+            match change.change {
+                SyncKind::DeletedElsewhere => {
+                    // Unless they are still assigned to another scheduled message of the role.
+                    let still_assigned: Vec<String> = known
+                        .iter()
+                        .chain(changes.iter().map(|change| &change.entry))
+                        .filter(|entry| {
+                            entry.role.as_deref() == Some(role_name)
+                                && entry.status == HistoryStatus::Scheduled
+                                && messages.iter().any(|message| message.id == entry.id)
+                        })
+                        .flat_map(|entry| entry.members.clone())
+                        .collect();
+                    if let Some(role) = self.config.role_mut(role_name) {
+                        role.selected.retain(|member| {
+                            !change.entry.members.contains(member)
+                                || still_assigned.contains(member)
+                        });
+                    }
+                }
+                SyncKind::Imported => {
+                    for member in &change.entry.members {
+                        self.record_selection(role_name, member);
+                    }
+                }
+                SyncKind::Posted => (),
+            }
+        }
+        self.record_history(changes.iter().map(|change| change.entry.clone()).collect());
+        changes
+    }
+
+    /// Scheduled messages of the channel, by posting time.
+    pub async fn scheduled_messages(&self) -> Vec<ScheduledMessageObject> {
         let mut messages = self.list_scheduled_messages(&self.config.channel).await;
         info!(
-            "Printing {} scheduled messages for channel {}",
+            "Found {} scheduled messages for channel {}",
             messages.len(),
             self.config.channel
         );
         messages.sort_by(|a, b| a.post_at.cmp(&b.post_at));
-        for mess in messages {
-            println!("{}", mess);
-        }
+        messages
     }
 
-    pub async fn cancel_scheduled_message(self, id_list: Vec<&str>) {
+    pub async fn cancel_scheduled_message(&self, id_list: Vec<&str>) -> Vec<Cancellation> {
         let messages = self.list_scheduled_messages(&self.config.channel).await;
         // let messages = api::list_scheduled_messages(&self.client, &self.config.channel).await;
         debug!("Filtering from {} messages", messages.len());
+        let mut cancellations = Vec::new();
         for id in id_list {
             let lookup = messages.iter().find(|mess| mess.id == id);
-            let message = match lookup {
+            let status = match lookup {
                 Some(mess) => {
-                    println!("Found message: {}", mess);
-                    println!("Please confirm cancellation: Y/n");
-                    if yes() {
-                        mess
+                    let question = format!("Found message: {}\nPlease confirm cancellation:", mess);
+                    if self.prompt.confirm(&question, false) {
+                        let request =
+                            api::DeleteScheduledMessageRequest::new(&self.config.channel, &mess.id);
+                        match self.api.delete_scheduled_message(&request).await.content {
+                            SlackApiContent::Ok(_empty) => {
+                                self.record_history(vec![self.previous_entry(
+                                    mess,
+                                    None,
+                                    None,
+                                    HistoryStatus::Cancelled,
+                                )]);
+                                Ok(CancelStatus::Cancelled)
+                            }
+                            SlackApiContent::Err(err) => Err(format!("{:?}", err.error)),
+                        }
                     } else {
-                        warn!("Scheduled message kept.");
-                        continue;
+                        Ok(CancelStatus::Kept)
                     }
                 }
-                None => {
-                    error!("No scheduled message with id \"{}\"", id);
-                    continue;
-                }
+                None => Ok(CancelStatus::NotFound),
             };
-            let request =
-                api::DeleteScheduledMessageRequest::new(&self.config.channel, &message.id);
-            let response = self.api.delete_scheduled_message(&request).await;
-            match response.content {
-                SlackApiContent::Ok(_empty) => println!("Deleted message with id {}", id),
-                SlackApiContent::Err(err) => error!("Failed to delete: {:?}", err),
-            }
+            cancellations.push(match status {
+                Ok(status) => Cancellation {
+                    id: id.to_string(),
+                    status,
+                    error: None,
+                },
+                Err(error) => Cancellation {
+                    id: id.to_string(),
+                    status: CancelStatus::Failed,
+                    error: Some(error),
+                },
+            });
         }
+        cancellations
+    }
+}
+
+/// Finds the assignment of the role for the same target date, among the bot's own scheduled assignments.
+/// Assignments of other roles aren't conflicts. Assignments the history doesn't know of, such as those of other operators,
+/// don't tell their role nor target date, so they conflict when posted on the same day.
+fn find_conflict<'m>(
+    role_name: &str,
+    target_date: &DateTime<Local>,
+    post_at: &DateTime<Local>,
+    messages: &'m [ScheduledMessageObject],
+) -> Option<&'m ScheduledMessageObject> {
+    let target_day = target_date.naive_local().date();
+    messages.iter().find(|message| match message.assignment() {
+        Some(assignment) => assignment.role == role_name && assignment.target_date == target_day,
+        None => !message.mentioned_members().is_empty() && message.date() == post_at.date(),
+    })
+}
+
+/// Scheduled messages which aren't assignments, posted on the target or posting day. Worth a mention, but not a conflict.
+fn unrelated_messages_on_day<'m>(
+    target_date: &DateTime<Local>,
+    post_at: &DateTime<Local>,
+    messages: &'m [ScheduledMessageObject],
+) -> Vec<&'m ScheduledMessageObject> {
+    messages
+        .iter()
+        .filter(|message| {
+            message.assigned_members().is_empty()
+                && (message.date() == target_date.date() || message.date() == post_at.date())
+        })
+        .collect()
+}
+
+/// Target date of the role on that day, at its assignment time.
+fn target_datetime(role: &RoleConfig, day: NaiveDate) -> Option<DateTime<Local>> {
+    Local
+        .from_local_datetime(&day.and_time(role.assignment_time()))
+        .single()
+}
+
+/// Draws distinct members of the role with the given RNG, following the rotation and ignoring the excluded ones.
+/// For a given seeded RNG, the draw is reproducible as long as the members and rotation state are the same.
+/// Returns `None` if there aren't enough members to draw from.
+fn select_random_members(
+    role: &RoleConfig,
+    rng: &mut StdRng,
+    exclude: &[String],
+    count: usize,
+) -> Option<Vec<String>> {
+    debug!("Selecting {} members", count);
+    // Each pick moves the rotation forward for the next one.
+    let mut rotation = role.clone();
+    let mut excluded = exclude.to_vec();
+    let mut picked = Vec::new();
+    for _ in 0..count {
+        let member = rotation.candidates(&excluded).choose(rng)?.to_string();
+        rotation.record_selection(&member);
+        excluded.push(member.clone());
+        picked.push(member);
+    }
+    Some(picked)
+}
+
+/// Default seed of a draw, derived from the target date and channel.
+/// Other roles add their name, and rerolls get their own seed so the first pick isn't the original draw all over again.
+fn draw_seed(role_name: &str, target_date: &DateTime<Local>, channel: &str, reroll: bool) -> u64 {
+    let date = target_date.naive_local().date().to_string();
+    let mut parts = vec![date.as_str(), channel];
+    if role_name != JOKE_ROLE {
+        parts.push(role_name);
+    }
+    if reroll {
+        parts.push("reroll");
     }
+    derive_seed(&parts)
+}
+
+/// Derives a draw seed from its parts with a 64 bits FNV-1a hash.
+/// Unlike the std hasher, it is stable across platforms and Rust versions, so anyone can derive it again.
+pub fn derive_seed(parts: &[&str]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+    let joined = parts.join("|");
+    joined.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
 }
 
 fn today_with_set_time(time: NaiveTime) -> DateTime<Local> {
@@ -544,23 +2281,17 @@ fn today_with_set_time(time: NaiveTime) -> DateTime<Local> {
         .expect("Couldn't generate time")
 }
 
-pub(crate) fn yes() -> bool {
-    let mut buff = String::new();
-    match std::io::stdin().read_line(&mut buff) {
-        Ok(_bytes) => buff.to_ascii_lowercase().trim() == "y",
-        Err(_err) => false,
-    }
-}
-
 #[cfg(test)]
 mod test {
     use std::borrow::Borrow;
+    use std::collections::BTreeMap;
 
     use super::*;
-    use crate::api::TestSlackClient;
+    use crate::api::{scheduled_message, TestSlackClient};
     use crate::dates::IsWeekday;
     use async_std::task;
     use chrono::prelude::*;
+    use history::history_entry;
 
     fn custom_bot(target_time_str: &str, post_time_str: &str) -> SlackBot<TestSlackClient> {
         let target_time = target_time_str.parse::<NaiveTime>().unwrap();
@@ -568,28 +2299,40 @@ mod test {
         let api = TestSlackClient::default();
 
         let config = BotConfig {
-            members: vec![
-                "user_1".to_string(),
-                "user_2".to_string(),
-                "user3".to_string(),
-            ],
-            selected: vec![],
+            joke: RoleConfig {
+                members: vec![
+                    "user_1".to_string(),
+                    "user_2".to_string(),
+                    "user3".to_string(),
+                ],
+                selected: vec![],
+                disabled: vec![],
+                target_time,
+                schedule: None,
+                post_time,
+                advance_days: 1,
+                templates: vec![],
+                label: None,
+                members_per_assignment: 1,
+                cc: None,
+                member_usergroup: None,
+            },
             channel: "test_channel".to_string(),
-            target_time,
-            post_time,
-            advance_days: 1,
             instant_delay: 45,
             token: Some("test_token".to_string()),
             id: "test_bot_id".to_string(),
+            announce_seed: false,
+            locale: Locale::En,
+            roles: BTreeMap::new(),
         };
-        SlackBot { api, config }
+        SlackBot::new(config, api)
     }
 
     #[test]
     fn get_target_date_default() {
         let bot = custom_bot("11:30:00", "11:30:00");
-        assert_eq!(bot.config.target_time, NaiveTime::from_hms(11, 30, 00));
-        let target_date = bot.get_target_dates(vec!["2021-12-31"]);
+        assert_eq!(bot.config.joke.target_time, NaiveTime::from_hms(11, 30, 00));
+        let target_date = bot.get_target_dates(&bot.config.joke, vec!["2021-12-31"]);
         let expected = Local.ymd(2021, 12, 31).and_hms(11, 30, 00);
         assert_eq!(target_date.first().unwrap().to_owned(), expected)
     }
@@ -597,7 +2340,7 @@ mod test {
     #[test]
     fn test_joke_success() {
         let post_time_config = NaiveTime::from_hms(1, 2, 3);
-        let bot = custom_bot("02:03:04", &post_time_config.to_string());
+        let mut bot = custom_bot("02:03:04", &post_time_config.to_string());
         let mut next_weekday = Local::now().date().naive_local().succ();
         while !next_weekday.is_weekday() {
             next_weekday = next_weekday.succ();
@@ -607,7 +2350,11 @@ mod test {
 
         let input_date_arg = vec![tomorrow.borrow()];
         // assert_eq!(input_date_arg, "2021-01-21");
-        let jokes = task::block_on(bot.joke(input_date_arg, None));
+        let options = ScheduleOptions {
+            days: input_date_arg,
+            ..ScheduleOptions::default()
+        };
+        let jokes = task::block_on(bot.joke(options));
         for joke in jokes {
             assert!(joke.target_date.is_weekday());
             assert!(joke.post_at.is_weekday());
@@ -615,4 +2362,698 @@ mod test {
             assert_eq!(joke.post_at.minute(), post_time_config.minute());
         }
     }
+
+    #[test]
+    fn fill_schedules_each_missing_day() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        let next_week = Local::now().date().naive_local() + Duration::days(7);
+        let days = crate::dates::next_working_days(next_week, 3);
+        let filled = task::block_on(bot.fill(JOKE_ROLE, &days)).unwrap();
+        let target_days: Vec<NaiveDate> = filled
+            .scheduled
+            .iter()
+            .map(|assignment| assignment.target_date.naive_local().date())
+            .collect();
+        assert_eq!(target_days, days);
+        assert_eq!(filled.failed, 0);
+
+        let unknown_role = task::block_on(bot.fill("standup", &days)).unwrap();
+        assert!(unknown_role.scheduled.is_empty());
+    }
+
+    #[test]
+    fn fill_counts_failed_schedules() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.api = TestSlackClient::failing_schedules();
+        let next_week = Local::now().date().naive_local() + Duration::days(7);
+        let days = crate::dates::next_working_days(next_week, 3);
+        let filled = task::block_on(bot.fill(JOKE_ROLE, &days)).unwrap();
+        assert!(filled.scheduled.is_empty());
+        assert_eq!(filled.failed, 3);
+        assert!(bot.config.joke.selected.is_empty());
+    }
+
+    #[test]
+    fn fill_keeps_weekends_of_daily_schedule() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.set_schedule(JOKE_ROLE, "daily at 10:00");
+        let next_week = Local::now().date().naive_local() + Duration::days(7);
+        let friday = next_week
+            .iter_days()
+            .find(|day| day.weekday() == Weekday::Fri)
+            .unwrap();
+        let days = vec![friday, friday.succ(), friday.succ().succ()];
+        let filled = task::block_on(bot.fill(JOKE_ROLE, &days)).unwrap();
+        assert_eq!(filled.failed, 0);
+        let target_days: Vec<NaiveDate> = filled
+            .scheduled
+            .iter()
+            .map(|assignment| assignment.target_date.naive_local().date())
+            .collect();
+        assert_eq!(target_days, days);
+        assert_eq!(
+            filled.scheduled[2].post_at.naive_local().date(),
+            friday.succ()
+        );
+    }
+
+    #[test]
+    fn joke_fills_next_scheduled_slots() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.set_schedule(JOKE_ROLE, "Tue,Thu 16:00");
+        let options = ScheduleOptions {
+            next: Some(2),
+            ..ScheduleOptions::default()
+        };
+        let jokes = task::block_on(bot.joke(options));
+        assert_eq!(jokes.len(), 2);
+        for joke in &jokes {
+            assert!(matches!(
+                joke.target_date.weekday(),
+                Weekday::Tue | Weekday::Thu
+            ));
+            assert_eq!(joke.target_date.hour(), 16);
+        }
+        assert_ne!(jokes[0].target_date, jokes[1].target_date);
+    }
+
+    #[test]
+    fn add_member_from_directory() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.set_prompt(Prompt::AssumeYes);
+        assert!(is_user_id("W012A3CDE"));
+        assert!(!is_user_id("spengler"));
+
+        let added = task::block_on(bot.add_member(JOKE_ROLE, "@Spengler"));
+        assert_eq!(added, Some("W012A3CDE".to_string()));
+        assert!(bot.config.joke.members.contains(&"W012A3CDE".to_string()));
+        // Already a member.
+        assert_eq!(
+            task::block_on(bot.add_member(JOKE_ROLE, "Egon Spengler")),
+            None
+        );
+        // Two users are named Ray, and none can be picked without input.
+        assert_eq!(task::block_on(bot.add_member(JOKE_ROLE, "ray")), None);
+        assert_eq!(task::block_on(bot.add_member(JOKE_ROLE, "nobody")), None);
+        assert_eq!(
+            task::block_on(bot.add_member(JOKE_ROLE, "Ray Parker")),
+            Some("U061F7AUR".to_string())
+        );
+        assert_eq!(bot.config.joke.members.len(), 5);
+    }
+
+    #[test]
+    fn import_members_from_usergroup() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.set_prompt(Prompt::AssumeYes);
+        let users = task::block_on(bot.usergroup_users("@Jokes-Committee")).unwrap();
+        assert_eq!(
+            users,
+            vec!["W012A3CDE".to_string(), "U061F7AUR".to_string()]
+        );
+        assert_eq!(task::block_on(bot.usergroup_users("@nobody")), None);
+
+        // The test client finds the same user for any ID or email.
+        let inputs = vec![
+            "W012A3CDE".to_string(),
+            "spengler@ghostbusters.example.com".to_string(),
+            "W012A3CDE".to_string(),
+        ];
+        let summary = task::block_on(bot.import_members(JOKE_ROLE, inputs.clone())).unwrap();
+        assert!(summary.applied);
+        assert_eq!(summary.found.len(), 1);
+        assert_eq!(summary.found[0].id, "W012A3CDE");
+        assert_eq!(bot.config.joke.members.len(), 4);
+
+        let summary = task::block_on(bot.import_members(JOKE_ROLE, inputs)).unwrap();
+        assert!(!summary.applied);
+        assert_eq!(summary.already_present.len(), 2);
+        assert_eq!(bot.config.joke.members.len(), 4);
+
+        // IDs are found in the user directory, without asking anything.
+        let inputs = vec!["U061F7AUR".to_string(), "U0NOBODY00".to_string()];
+        let summary = task::block_on(bot.import_members(JOKE_ROLE, inputs)).unwrap();
+        assert_eq!(summary.found[0].id, "U061F7AUR");
+        assert_eq!(summary.not_found, vec!["U0NOBODY00".to_string()]);
+    }
+
+    #[test]
+    fn usergroup_members_and_cc() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.config.joke.selected = vec!["user_1".to_string(), "U061F7AUR".to_string()];
+        bot.config.joke.members.push("U061F7AUR".to_string());
+        let excluded = task::block_on(bot.set_member_usergroup(JOKE_ROLE, "@jokes-committee"));
+        assert!(excluded.is_empty());
+        assert_eq!(
+            bot.config.joke.members,
+            vec!["W012A3CDE".to_string(), "U061F7AUR".to_string()]
+        );
+        assert_eq!(bot.config.joke.selected, vec!["U061F7AUR".to_string()]);
+
+        task::block_on(bot.set_cc(JOKE_ROLE, "S0614TZR7"));
+        let cc = bot.config.joke.cc.clone().unwrap();
+        assert_eq!(cc.handle, "jokes-committee");
+        let options = ScheduleOptions {
+            days: vec!["2050-02-15"],
+            ..ScheduleOptions::default()
+        };
+        let jokes = task::block_on(bot.joke(options));
+        assert_eq!(jokes.len(), 1);
+        assert_eq!(jokes[0].members, vec!["W012A3CDE".to_string()]);
+        assert!(jokes[0].message.ends_with(" cc <!subteam^S0614TZR7>"));
+
+        task::block_on(bot.set_cc(JOKE_ROLE, "none"));
+        task::block_on(bot.set_cc(JOKE_ROLE, "@nobody"));
+        assert_eq!(bot.config.joke.cc, None);
+    }
+
+    #[test]
+    fn printed_config_hides_token() {
+        let bot = custom_bot("11:30:00", "11:30:00");
+        let printed = serde_json::to_value(bot.settings().redacted()).unwrap();
+        assert!(printed.get("token").is_none());
+        assert_eq!(printed["channel"], "test_channel");
+        assert_eq!(bot.settings().token.as_deref(), Some("test_token"));
+    }
+
+    #[test]
+    fn disable_enable_and_remove_members() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.config.joke.selected = vec!["user_2".to_string()];
+        let updates = task::block_on(bot.set_members_disabled("joke", vec!["user3"], true));
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].action, MemberAction::Disabled);
+        assert_eq!(bot.config.joke.disabled, vec!["user3".to_string()]);
+        // Disabling twice keeps a single entry, unknown members are skipped.
+        task::block_on(bot.set_members_disabled("joke", vec!["user3", "nobody"], true));
+        assert_eq!(bot.config.joke.disabled, vec!["user3".to_string()]);
+
+        let members = task::block_on(bot.list_members("joke"));
+        let flags: Vec<(bool, bool)> = members.iter().map(|m| (m.disabled, m.selected)).collect();
+        assert_eq!(flags, vec![(false, false), (false, true), (true, false)]);
+
+        task::block_on(bot.set_members_disabled("joke", vec!["user3"], false));
+        assert!(bot.config.joke.disabled.is_empty());
+
+        let updates = task::block_on(bot.remove_members("joke", vec!["user_2"]));
+        assert_eq!(updates[0].action, MemberAction::Removed);
+        assert_eq!(
+            bot.config.joke.members,
+            vec!["user_1".to_string(), "user3".to_string()]
+        );
+        assert!(bot.config.joke.selected.is_empty());
+    }
+
+    #[test]
+    fn sync_members_with_channel() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.config.joke.members.push("U023BECGF".to_string());
+        bot.config.joke.selected = vec!["user_1".to_string(), "U023BECGF".to_string()];
+        bot.add_role("standup", None);
+
+        bot.set_prompt(Prompt::NoInput);
+        let declined =
+            task::block_on(bot.sync_members(JOKE_ROLE, ImportFilter::default())).unwrap();
+        assert!(!declined.applied);
+        assert_eq!(bot.config.joke.members.len(), 4);
+
+        bot.set_prompt(Prompt::AssumeYes);
+        let diff = task::block_on(bot.sync_members(JOKE_ROLE, ImportFilter::default())).unwrap();
+        assert!(diff.applied);
+        assert_eq!(diff.added.len(), 3);
+        assert_eq!(diff.removed.len(), 3);
+        assert_eq!(
+            bot.config.joke.members,
+            vec!["U023BECGF", "U061F7AUR", "W012A3CDE", "W012A3CDA"]
+        );
+        assert_eq!(bot.config.joke.selected, vec!["U023BECGF"]);
+        assert_eq!(bot.config.roles["standup"].members, vec!["U023BECGF"]);
+    }
+
+    #[test]
+    fn find_scheduled_assignment_by_post_date() {
+        let bot = custom_bot("11:30:00", "11:30:00");
+        let messages = vec![
+            scheduled_message(
+                "1",
+                Local.ymd(2021, 12, 29).and_hms(11, 30, 0),
+                "<@user_1> joke",
+            ),
+            scheduled_message("2", Local.ymd(2021, 12, 30).and_hms(9, 0, 0), "No mention"),
+            scheduled_message(
+                "3",
+                Local.ymd(2021, 12, 30).and_hms(11, 30, 0),
+                "<@user_2> joke",
+            ),
+        ];
+        let target_date = Local.ymd(2021, 12, 31).and_hms(11, 30, 0);
+        let found = bot
+            .find_scheduled_assignment(JOKE_ROLE, &bot.config.joke, &target_date, &messages)
+            .unwrap();
+        assert_eq!(found.id, "3");
+        assert_eq!(found.mentioned_members(), vec!["user_2"]);
+
+        let monday = Local.ymd(2022, 1, 3).and_hms(11, 30, 0);
+        assert!(bot
+            .find_scheduled_assignment(JOKE_ROLE, &bot.config.joke, &monday, &messages)
+            .is_none());
+    }
+
+    #[test]
+    fn joke_assigns_given_member() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        let path =
+            std::env::temp_dir().join(format!("slack-r-given-member-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        bot.set_history(History::new(path.clone()));
+        let options = ScheduleOptions {
+            days: vec!["2050-02-15"],
+            members: vec!["user_2"],
+            ..ScheduleOptions::default()
+        };
+        let jokes = task::block_on(bot.joke(options));
+        assert_eq!(jokes.len(), 1);
+        assert_eq!(jokes[0].members, vec!["user_2".to_string()]);
+        assert_eq!(bot.config.joke.selected, vec!["user_2".to_string()]);
+        let recorded = History::new(path.clone()).current();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].members, vec!["user_2".to_string()]);
+
+        // One member given for a pair.
+        let options = ScheduleOptions {
+            days: vec!["2050-02-16"],
+            members: vec!["user_1"],
+            members_per_assignment: Some(2),
+            ..ScheduleOptions::default()
+        };
+        assert!(task::block_on(bot.joke(options)).is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn joke_reports_unrelated_messages() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        let role = bot.config.joke.clone();
+        let target_date = bot.get_target_dates(&role, vec!["2050-02-15"])[0];
+        let post_at = bot.get_post_at_date(&role, &target_date, None);
+        bot.api
+            .schedule("reminder", post_at.timestamp(), "Team lunch tomorrow");
+        let options = ScheduleOptions {
+            days: vec!["2050-02-15"],
+            ..ScheduleOptions::default()
+        };
+        let jokes = task::block_on(bot.joke(options));
+        assert_eq!(jokes.len(), 1);
+        assert_eq!(jokes[0].unrelated, vec!["reminder".to_string()]);
+        assert!(jokes[0]
+            .to_string()
+            .ends_with(". Also scheduled around that day: reminder"));
+    }
+
+    #[test]
+    fn find_scheduled_assignment_from_history() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        let target_date = Local.ymd(2021, 12, 31).and_hms(11, 30, 0);
+        let post_at = Local.ymd(2021, 12, 30).and_hms(11, 30, 0);
+        let path = std::env::temp_dir().join(format!(
+            "slack-r-known-assignments-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        bot.set_history(History::new(path.clone()));
+        let recorded = |id: &str, role: &str, post_at: DateTime<Local>| HistoryEntry {
+            role: Some(role.to_string()),
+            post_at,
+            ..history_entry(id, &["user_3"], target_date, HistoryStatus::Scheduled)
+        };
+        bot.record_history(vec![
+            recorded("standup", "standup", post_at),
+            recorded("joke", JOKE_ROLE, post_at - Duration::days(2)),
+        ]);
+        let tagged =
+            |id: &str, post_at: DateTime<Local>| scheduled_message(id, post_at, "<@user_3> joke");
+        // Another role on the same post date, and a joke posted earlier than usual for that date.
+        let messages = bot.with_known_assignments(vec![
+            tagged("standup", post_at),
+            tagged("joke", post_at - Duration::days(2)),
+            tagged("unknown", post_at - Duration::days(1)),
+        ]);
+        assert_eq!(messages[2].assignment(), None);
+        let found = bot
+            .find_scheduled_assignment(JOKE_ROLE, &bot.config.joke, &target_date, &messages)
+            .unwrap();
+        assert_eq!(found.id, "joke");
+        assert_eq!(found.assigned_members(), vec!["user_3"]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn only_own_assignments_conflict() {
+        let target_date = Local.ymd(2021, 12, 31).and_hms(11, 30, 0);
+        let post_at = Local.ymd(2021, 12, 30).and_hms(11, 30, 0);
+        let message = |id: &str,
+                       post_at: DateTime<Local>,
+                       text: &str,
+                       assignment: Option<AssignmentMetadata>| {
+            let message = scheduled_message(id, post_at, text);
+            match assignment {
+                Some(assignment) => message.with_assignment(assignment),
+                None => message,
+            }
+        };
+        let metadata = |role: &str| {
+            Some(AssignmentMetadata {
+                role: role.to_string(),
+                target_date: target_date.naive_local().date(),
+                members: vec!["user_3".to_string()],
+                seed: 42,
+            })
+        };
+        let reminder = || message("reminder", post_at, "Team lunch tomorrow", None);
+        let messages = vec![
+            reminder(),
+            message("standup", post_at, "<@user_3>", metadata("standup")),
+        ];
+        assert!(find_conflict(JOKE_ROLE, &target_date, &post_at, &messages).is_none());
+        let unrelated = unrelated_messages_on_day(&target_date, &post_at, &messages);
+        assert_eq!(unrelated.len(), 1);
+        assert_eq!(unrelated[0].id, "reminder");
+
+        // The joke assignment for that date, posted earlier than usual.
+        let joke = message(
+            "joke",
+            post_at - Duration::days(2),
+            "<@user_3>",
+            metadata(JOKE_ROLE),
+        );
+        // Scheduled by an older version, without metadata.
+        let legacy = message("legacy", post_at, "<@user_1> joke", None);
+        let conflict = |messages: Vec<ScheduledMessageObject>| {
+            find_conflict(JOKE_ROLE, &target_date, &post_at, &messages).map(|m| m.id.clone())
+        };
+        assert_eq!(conflict(vec![reminder(), joke]), Some("joke".to_string()));
+        assert_eq!(
+            conflict(vec![reminder(), legacy]),
+            Some("legacy".to_string())
+        );
+    }
+
+    #[test]
+    fn reroll_replaces_in_place() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.set_prompt(Prompt::AssumeYes);
+        bot.config.joke.selected = vec!["user_1".to_string()];
+        let role = bot.config.joke.clone();
+        let target_date = bot.get_target_dates(&role, vec!["2050-02-15"])[0];
+        let post_at = bot.get_post_at_date(&role, &target_date, None);
+        bot.api
+            .schedule("original", post_at.timestamp(), "<@user_1> joke");
+
+        let rerolled =
+            task::block_on(bot.reroll(JOKE_ROLE, Some("2050-02-15"), None, vec![])).unwrap();
+        assert_eq!(rerolled.post_at, post_at);
+        assert_eq!(rerolled.replaces.as_deref(), Some("original"));
+        assert_eq!(rerolled.members.len(), 1);
+        assert!(!rerolled.members.contains(&"user_1".to_string()));
+        assert_eq!(bot.api.deleted(), vec!["original"]);
+        assert_eq!(bot.config.joke.selected, rerolled.members);
+    }
+
+    #[test]
+    fn reroll_keeps_original_when_scheduling_fails() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.api = TestSlackClient::failing_schedules();
+        bot.set_prompt(Prompt::AssumeYes);
+        bot.config.joke.selected = vec!["user_1".to_string()];
+        let role = bot.config.joke.clone();
+        let target_date = bot.get_target_dates(&role, vec!["2050-02-15"])[0];
+        let post_at = bot.get_post_at_date(&role, &target_date, None);
+        bot.api
+            .schedule("original", post_at.timestamp(), "<@user_1> joke");
+
+        assert!(task::block_on(bot.reroll(JOKE_ROLE, Some("2050-02-15"), None, vec![])).is_none());
+        assert!(bot.api.deleted().is_empty());
+        assert_eq!(bot.config.joke.selected, vec!["user_1".to_string()]);
+    }
+
+    #[test]
+    fn swap_needs_both_assignments() {
+        let bot = custom_bot("11:30:00", "11:30:00");
+        let swapped = task::block_on(bot.swap(JOKE_ROLE, "2021-12-30", "2021-12-31"));
+        assert!(swapped.is_empty());
+    }
+
+    /// Bot with the joke of two dates scheduled, by user_1 and user_2, as known from the history.
+    fn bot_with_two_jokes(api: TestSlackClient, days: [&str; 2]) -> SlackBot<TestSlackClient> {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.api = api;
+        let path = std::env::temp_dir().join(format!(
+            "slack-r-two-jokes-{}-{}.jsonl",
+            days[0],
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        bot.set_history(History::new(path));
+        let role = bot.config.joke.clone();
+        let mut entries = Vec::new();
+        for (index, (id, member)) in [("first", "user_1"), ("second", "user_2")]
+            .iter()
+            .enumerate()
+        {
+            let target_date = bot.get_target_dates(&role, vec![days[index]])[0];
+            let post_at = bot.get_post_at_date(&role, &target_date, None);
+            bot.api
+                .schedule(id, post_at.timestamp(), &format!("<@{}> joke", member));
+            entries.push(HistoryEntry {
+                post_at,
+                seed: Some(index as u64 + 7),
+                ..history_entry(id, &[member], target_date, HistoryStatus::Scheduled)
+            });
+        }
+        bot.record_history(entries);
+        bot
+    }
+
+    #[test]
+    fn swap_exchanges_members_and_seeds() {
+        let days = ["2050-02-15", "2050-02-16"];
+        let bot = bot_with_two_jokes(TestSlackClient::default(), days);
+        let swapped = task::block_on(bot.swap(JOKE_ROLE, days[0], days[1]));
+        assert_eq!(swapped.len(), 2);
+        assert_eq!(swapped[0].members, vec!["user_2".to_string()]);
+        assert_eq!(swapped[0].seed, 8);
+        assert_eq!(swapped[1].members, vec!["user_1".to_string()]);
+        assert_eq!(swapped[1].seed, 7);
+        assert_eq!(bot.api.deleted(), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn swap_rolls_back_when_second_schedule_fails() {
+        let days = ["2050-03-15", "2050-03-16"];
+        let bot = bot_with_two_jokes(TestSlackClient::failing_schedules_after(1), days);
+        let swapped = task::block_on(bot.swap(JOKE_ROLE, days[0], days[1]));
+        assert!(swapped.is_empty());
+        // Only the first replacement is deleted again.
+        assert_eq!(bot.api.deleted(), vec!["Q1298393284"]);
+        let scheduled: Vec<String> = task::block_on(bot.list_scheduled_messages("test_channel"))
+            .into_iter()
+            .map(|message| message.id)
+            .collect();
+        assert!(scheduled.contains(&"first".to_string()));
+        assert!(scheduled.contains(&"second".to_string()));
+        assert!(!scheduled.contains(&"Q1298393284".to_string()));
+    }
+
+    #[test]
+    fn results_json_fields_are_stable() {
+        let target_date = Local.ymd(2022, 2, 15).and_hms(11, 30, 0);
+        let assignment = Assignment {
+            role: JOKE_ROLE.to_string(),
+            members: vec!["user_1".to_string()],
+            message: "<@user_1> joke".to_string(),
+            target_date,
+            post_at: target_date - Duration::days(1),
+            id: "Q1298393284".to_string(),
+            seed: 42,
+            replaces: None,
+            unrelated: Vec::new(),
+        };
+        let json = serde_json::to_value(&assignment).unwrap();
+        let mut keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                "id",
+                "members",
+                "message",
+                "post_at",
+                "role",
+                "seed",
+                "target_date"
+            ]
+        );
+        let cancellation = Cancellation {
+            id: "Q1298393284".to_string(),
+            status: CancelStatus::NotFound,
+            error: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&cancellation).unwrap(),
+            serde_json::json!({"id": "Q1298393284", "status": "not_found"})
+        );
+    }
+
+    #[test]
+    fn derive_seed_is_stable() {
+        // Reference FNV-1a values, which must never change or past draws can't be checked anymore.
+        assert_eq!(derive_seed(&[""]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(derive_seed(&["a"]), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(
+            derive_seed(&["2022-02-15", "test_channel"]),
+            derive_seed(&["2022-02-15", "other_channel"])
+        );
+    }
+
+    #[test]
+    fn seeded_draw_is_reproducible() {
+        let bot = custom_bot("11:30:00", "11:30:00");
+        for seed in 0..20 {
+            let first =
+                select_random_members(&bot.config.joke, &mut StdRng::seed_from_u64(seed), &[], 1);
+            let second =
+                select_random_members(&bot.config.joke, &mut StdRng::seed_from_u64(seed), &[], 1);
+            assert!(first.is_some());
+            assert_eq!(first, second);
+        }
+    }
+
+    #[test]
+    fn draw_skips_excluded_members() {
+        let bot = custom_bot("11:30:00", "11:30:00");
+        let mut rng = StdRng::seed_from_u64(42);
+        let exclude = vec!["user_1".to_string(), "user_2".to_string()];
+        assert_eq!(
+            select_random_members(&bot.config.joke, &mut rng, &exclude, 1),
+            Some(vec!["user3".to_string()])
+        );
+        let exclude_all = bot.config.joke.members.clone();
+        assert_eq!(
+            select_random_members(&bot.config.joke, &mut rng, &exclude_all, 1),
+            None
+        );
+    }
+
+    #[test]
+    fn pair_draw_picks_distinct_members() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.config.joke.selected = vec!["user_1".to_string()];
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let pair = select_random_members(&bot.config.joke, &mut rng, &[], 2).unwrap();
+            assert_eq!(pair.len(), 2);
+            assert_ne!(pair[0], pair[1]);
+            // Both fresh members go before anyone gets a second turn.
+            assert!(!pair.contains(&"user_1".to_string()));
+        }
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            select_random_members(&bot.config.joke, &mut rng, &[], 4),
+            None
+        );
+    }
+
+    #[test]
+    fn find_member_from_id_email_and_name() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.set_prompt(Prompt::NoInput);
+        let role = bot.config.joke.clone();
+        assert_eq!(
+            task::block_on(bot.find_member(&role, "user_2")),
+            Some("user_2".to_string())
+        );
+        // The test client always answers with user W012A3CDE, named spengler.
+        assert_eq!(
+            task::block_on(bot.find_member(&role, "spengler@ghostbusters.example.com")),
+            None
+        );
+        bot.config.joke.members.push("W012A3CDE".to_string());
+        let role = bot.config.joke.clone();
+        assert_eq!(
+            task::block_on(bot.find_member(&role, "spengler@ghostbusters.example.com")),
+            Some("W012A3CDE".to_string())
+        );
+        assert_eq!(
+            task::block_on(bot.find_member(&role, "@Spengler")),
+            Some("W012A3CDE".to_string())
+        );
+        assert_eq!(task::block_on(bot.find_member(&role, "venkman")), None);
+        // Two users are named Ray, and none can be picked without input.
+        bot.config.joke.members.push("U061F7AUR".to_string());
+        let role = bot.config.joke.clone();
+        assert_eq!(task::block_on(bot.find_member(&role, "ray")), None);
+        assert_eq!(
+            task::block_on(bot.find_member(&role, "Ray Parker")),
+            Some("U061F7AUR".to_string())
+        );
+    }
+
+    #[test]
+    fn compose_message_localized_default() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.config.locale = Locale::Fr;
+        let target_date = Local.ymd(2022, 2, 15).and_hms(11, 30, 0);
+        let post_at = Local.ymd(2022, 2, 14).and_hms(11, 30, 0);
+        let mut rng = StdRng::seed_from_u64(1);
+        let text = task::block_on(bot.compose_message(
+            JOKE_ROLE,
+            &bot.config.joke,
+            &mut rng,
+            &["W012A3CDE".to_string()],
+            &target_date,
+            &post_at,
+            false,
+            1,
+        ));
+        assert_eq!(
+            text,
+            "<@W012A3CDE> sera en charge d'une blague le mardi 15 février !"
+        );
+    }
+
+    #[test]
+    fn compose_message_from_template() {
+        let mut bot = custom_bot("11:30:00", "11:30:00");
+        bot.config.joke.templates = vec!["{mention} ({name}) jokes on {weekday}".to_string()];
+        let target_date = Local.ymd(2022, 2, 15).and_hms(11, 30, 0);
+        let post_at = Local.ymd(2022, 2, 14).and_hms(11, 30, 0);
+        let mut rng = StdRng::seed_from_u64(1);
+        let text = task::block_on(bot.compose_message(
+            JOKE_ROLE,
+            &bot.config.joke,
+            &mut rng,
+            &["W012A3CDE".to_string()],
+            &target_date,
+            &post_at,
+            false,
+            1,
+        ));
+        assert_eq!(text, "<@W012A3CDE> (spengler) jokes on Tuesday");
+
+        bot.config.announce_seed = true;
+        let text = task::block_on(bot.compose_message(
+            JOKE_ROLE,
+            &bot.config.joke,
+            &mut rng,
+            &["W012A3CDE".to_string()],
+            &target_date,
+            &post_at,
+            false,
+            1,
+        ));
+        assert_eq!(
+            text,
+            "<@W012A3CDE> (spengler) jokes on Tuesday (draw seed: 1)"
+        );
+    }
 }
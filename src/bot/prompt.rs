@@ -0,0 +1,100 @@
+use log::warn;
+use std::io::IsTerminal;
+
+/// How confirmations are answered, so the bot can run from cron jobs and CI.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Prompt {
+    /// Reads the answer from stdin.
+    #[default]
+    Interactive,
+    /// `--yes`: answers yes to every confirmation.
+    AssumeYes,
+    /// `--no-input`: never reads stdin, and takes the safe answer of each confirmation.
+    NoInput,
+}
+
+impl Prompt {
+    /// Picks the mode from the `--yes` and `--no-input` flags.
+    /// Without any of them, falls back to `NoInput` when stdin isn't a terminal, instead of blocking on it.
+    pub fn from_flags(assume_yes: bool, no_input: bool) -> Prompt {
+        if assume_yes {
+            Prompt::AssumeYes
+        } else if no_input {
+            Prompt::NoInput
+        } else if !std::io::stdin().is_terminal() {
+            warn!("Input is not a terminal, confirmations will take their default answer. Use --yes or --no-input to choose explicitly.");
+            Prompt::NoInput
+        } else {
+            Prompt::Interactive
+        }
+    }
+
+    /// Asks a yes/no question, on stderr so it doesn't get mixed with the output.
+    /// `default` is the answer used with `NoInput`.
+    pub fn confirm(&self, question: &str, default: bool) -> bool {
+        eprintln!("{} y/n", question);
+        match self {
+            Prompt::Interactive => yes(),
+            Prompt::AssumeYes => {
+                eprintln!("y (--yes)");
+                true
+            }
+            Prompt::NoInput => {
+                eprintln!("{} (no input)", if default { "y" } else { "n" });
+                default
+            }
+        }
+    }
+
+    /// Asks to pick one of the options by number, on stderr.
+    /// Without interactive input, none is picked: no option is a safe default.
+    pub fn choose(&self, question: &str, options: &[String]) -> Option<usize> {
+        eprintln!("{}", question);
+        for (index, option) in options.iter().enumerate() {
+            eprintln!("  {}. {}", index + 1, option);
+        }
+        match self {
+            Prompt::Interactive => {
+                eprintln!("Number of your choice, anything else to pick none:");
+                let mut buff = String::new();
+                match std::io::stdin().read_line(&mut buff) {
+                    Ok(_bytes) => buff
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|choice| (1..=options.len()).contains(choice))
+                        .map(|choice| choice - 1),
+                    Err(_err) => None,
+                }
+            }
+            Prompt::AssumeYes | Prompt::NoInput => {
+                eprintln!("None picked (no input)");
+                None
+            }
+        }
+    }
+}
+
+fn yes() -> bool {
+    let mut buff = String::new();
+    match std::io::stdin().read_line(&mut buff) {
+        Ok(_bytes) => buff.to_ascii_lowercase().trim() == "y",
+        Err(_err) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scripted_answers() {
+        assert!(Prompt::AssumeYes.confirm("Cancel?", false));
+        assert!(!Prompt::NoInput.confirm("Cancel?", false));
+        assert!(Prompt::NoInput.confirm("Pick it?", true));
+        let options = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(Prompt::AssumeYes.choose("Which one?", &options), None);
+        assert_eq!(Prompt::from_flags(true, true), Prompt::AssumeYes);
+        assert_eq!(Prompt::from_flags(false, true), Prompt::NoInput);
+    }
+}
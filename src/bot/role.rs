@@ -0,0 +1,228 @@
+use super::schedule::Schedule;
+use super::template::{default_template, MessageTemplate};
+use crate::dates::{IsWeekday, Locale};
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+/// Name of the original role, whose settings are kept at the top level of the config file.
+pub const JOKE_ROLE: &str = "joke";
+
+/// A Slack user group, saved with its handle so the config file stays readable. Mentioned by ID.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsergroupRef {
+    pub id: String,
+    /// Without the @.
+    pub handle: String,
+}
+
+impl UsergroupRef {
+    /// Slack mention of the group, notifying its members.
+    pub fn mention(&self) -> String {
+        format!("<!subteam^{}>", self.id)
+    }
+}
+
+/// Settings and rotation state of a rotating duty, such as the joke, the standup host or the release captain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoleConfig {
+    /// The list of members ids that can be selected. Adds all of the channel when added to a channel.
+    pub members: Vec<String>,
+    /// Members that have been selected in the current rotation. Cleared once everyone had their turn.
+    pub selected: Vec<String>,
+    /// Members temporarily out of the draw, such as during holidays. They keep their place in the rotation.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<String>,
+    /// As input only accepts dates, this is the time that will be applied to the input date.
+    /// Ignored when the role has a schedule.
+    pub target_time: NaiveTime,
+    /// Days and time needing an assignment, such as "Tue,Thu at 16:00". Every weekday at `target_time` when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<Schedule>,
+    /// Possible offset for the actual time at which the message will be posted, to give some leeway for the joke to be prepared.
+    /// How many days in avance to schedule the post, relative to the target time.
+    pub advance_days: i64,
+    /// On the day from `advance_days`, post at this time.
+    pub post_time: NaiveTime,
+    /// Variants of the announcement message, one being picked at random for each message.
+    /// See `MessageTemplate` for the available placeholders. Uses the locale's default template when empty.
+    pub templates: Vec<String>,
+    /// Name of the duty in the default templates, such as "standup host". Defaults to the role name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// How many distinct members are assigned together for each date.
+    pub members_per_assignment: usize,
+    /// User group mentioned in the announcements, where the template has `{cc}`, or after the message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cc: Option<UsergroupRef>,
+    /// User group the members are synced from before each draw, instead of being managed by hand.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member_usergroup: Option<UsergroupRef>,
+}
+
+impl Default for RoleConfig {
+    fn default() -> Self {
+        RoleConfig {
+            members: Vec::new(),
+            selected: Vec::new(),
+            disabled: Vec::new(),
+            target_time: NaiveTime::from_hms(11, 30, 0),
+            schedule: None,
+            post_time: NaiveTime::from_hms(11, 30, 0),
+            advance_days: 1,
+            templates: Vec::new(),
+            label: None,
+            members_per_assignment: 1,
+            cc: None,
+            member_usergroup: None,
+        }
+    }
+}
+
+impl RoleConfig {
+    /// Parses the configured templates, or the default one if none is configured.
+    /// `plural` picks the default template for assignments of several members.
+    pub fn message_templates(
+        &self,
+        role_name: &str,
+        locale: Locale,
+        plural: bool,
+    ) -> Result<Vec<MessageTemplate>, String> {
+        if self.templates.is_empty() {
+            return Ok(vec![MessageTemplate::parse(default_template(
+                locale,
+                role_name == JOKE_ROLE,
+                plural,
+            ))?]);
+        }
+        self.templates
+            .iter()
+            .map(|template| MessageTemplate::parse(template))
+            .collect()
+    }
+
+    /// Time of the day of the assignments: the schedule's, or `target_time`.
+    pub fn assignment_time(&self) -> NaiveTime {
+        match &self.schedule {
+            Some(schedule) => schedule.time,
+            None => self.target_time,
+        }
+    }
+
+    /// Whether the date needs an assignment: a day of the schedule, or any weekday without one.
+    pub fn is_target_day(&self, date: NaiveDate) -> bool {
+        match &self.schedule {
+            Some(schedule) => schedule.matches(date),
+            None => date.is_weekday(),
+        }
+    }
+
+    /// Dates needing an assignment after `after`, up to `until` included.
+    pub fn target_days(&self, after: NaiveDate, until: NaiveDate) -> Vec<NaiveDate> {
+        after
+            .iter_days()
+            .skip(1)
+            .take_while(|day| *day <= until)
+            .filter(|day| self.is_target_day(*day))
+            .collect()
+    }
+
+    pub fn label<'a>(&'a self, role_name: &'a str) -> &'a str {
+        self.label.as_deref().unwrap_or(role_name)
+    }
+
+    /// Members that can be drawn: those who haven't had their turn in the current rotation, without the excluded nor disabled ones.
+    /// Once everyone had their turn, everyone is a candidate again.
+    pub fn candidates(&self, exclude: &[String]) -> Vec<&String> {
+        let available: Vec<&String> = self
+            .members
+            .iter()
+            .filter(|member| !exclude.contains(member) && !self.disabled.contains(member))
+            .collect();
+        let fresh: Vec<&String> = available
+            .iter()
+            .filter(|member| !self.selected.contains(member))
+            .copied()
+            .collect();
+        if fresh.is_empty() {
+            available
+        } else {
+            fresh
+        }
+    }
+
+    /// Updates the rotation state, starting a new rotation once every member had their turn.
+    /// Disabled members don't hold the rotation back.
+    pub fn record_selection(&mut self, member: &str) {
+        let mut active = self
+            .members
+            .iter()
+            .filter(|member| !self.disabled.contains(member))
+            .peekable();
+        if active.peek().is_some() && active.all(|member| self.selected.contains(member)) {
+            self.selected.clear();
+        }
+        if !self.selected.iter().any(|selected| selected == member) {
+            self.selected.push(member.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn role() -> RoleConfig {
+        RoleConfig {
+            members: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            ..RoleConfig::default()
+        }
+    }
+
+    #[test]
+    fn candidates_skip_selected_until_rotation_ends() {
+        let mut role = role();
+        role.record_selection("a");
+        role.record_selection("b");
+        assert_eq!(role.candidates(&[]), vec!["c"]);
+        // Excluding the last fresh member falls back to everyone else.
+        assert_eq!(role.candidates(&["c".to_string()]), vec!["a", "b"]);
+
+        role.record_selection("c");
+        assert_eq!(role.selected, vec!["a", "b", "c"]);
+        role.record_selection("b");
+        assert_eq!(role.selected, vec!["b"]);
+        assert_eq!(role.candidates(&[]), vec!["a", "c"]);
+    }
+
+    #[test]
+    fn disabled_members_are_out_of_the_draw() {
+        let mut role = role();
+        role.disabled = vec!["b".to_string()];
+        assert_eq!(role.candidates(&[]), vec!["a", "c"]);
+        role.record_selection("a");
+        role.record_selection("c");
+        // The rotation ends without the disabled member.
+        role.record_selection("a");
+        assert_eq!(role.selected, vec!["a"]);
+    }
+
+    #[test]
+    fn target_days_follow_schedule() {
+        let friday = NaiveDate::from_ymd(2022, 2, 18);
+        let next_friday = NaiveDate::from_ymd(2022, 2, 25);
+        let mut role = role();
+        assert_eq!(role.target_days(friday, next_friday).len(), 5);
+        assert_eq!(role.assignment_time(), NaiveTime::from_hms(11, 30, 0));
+
+        role.schedule = Some("Tue,Thu 16:00".parse().unwrap());
+        assert_eq!(
+            role.target_days(friday, next_friday),
+            vec![
+                NaiveDate::from_ymd(2022, 2, 22),
+                NaiveDate::from_ymd(2022, 2, 24)
+            ]
+        );
+        assert_eq!(role.assignment_time(), NaiveTime::from_hms(16, 0, 0));
+    }
+}
@@ -0,0 +1,202 @@
+use chrono::{Datelike, NaiveDate, NaiveTime, Timelike, Weekday};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Display;
+use std::str::FromStr;
+
+const WEEK: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// Days of the week and time of the day a role needs an assignment for, such as "weekdays at 11:30" or "Tue,Thu 16:00".
+/// Days are a comma separated list of weekday names, ranges such as `Mon-Wed`, or one of `weekdays`, `weekends` and `daily`.
+/// The time is in 24h format, optionally preceded by `at`. Saved in the config file as the expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    /// Monday first, without duplicates.
+    days: Vec<Weekday>,
+    pub time: NaiveTime,
+}
+
+impl Schedule {
+    /// Whether the date needs an assignment.
+    pub fn matches(&self, date: NaiveDate) -> bool {
+        self.days.contains(&date.weekday())
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let lowercase = input.to_ascii_lowercase();
+        let mut words: Vec<&str> = lowercase.split_whitespace().collect();
+        let time_word = words
+            .pop()
+            .ok_or_else(|| "Empty schedule. Example: \"weekdays at 11:30\"".to_string())?;
+        let time = NaiveTime::parse_from_str(time_word, "%H:%M")
+            .or_else(|_e| NaiveTime::parse_from_str(time_word, "%H:%M:%S"))
+            .map_err(|_e| {
+                format!(
+                    "Schedule {} must end with a time such as 11:30, got {}",
+                    input, time_word
+                )
+            })?;
+        if words.last() == Some(&"at") {
+            words.pop();
+        }
+        // Allows spaces around commas, and "every day".
+        let days_spec = words.concat();
+        if days_spec.is_empty() {
+            return Err(format!(
+                "Schedule {} has no days. Example: \"Tue,Thu 16:00\"",
+                input
+            ));
+        }
+        let mut days = Vec::new();
+        for item in days_spec.split(',') {
+            days.extend(parse_days(item)?);
+        }
+        let days = WEEK
+            .iter()
+            .copied()
+            .filter(|day| days.contains(day))
+            .collect();
+        Ok(Schedule { days, time })
+    }
+}
+
+fn parse_days(item: &str) -> Result<Vec<Weekday>, String> {
+    match item {
+        "weekdays" | "weekday" => return Ok(WEEK[..5].to_vec()),
+        "weekends" | "weekend" => return Ok(WEEK[5..].to_vec()),
+        "daily" | "everyday" => return Ok(WEEK.to_vec()),
+        _ => (),
+    }
+    match item.split_once('-') {
+        Some((first, last)) => {
+            let first = parse_day(first)?.num_days_from_monday() as usize;
+            let last = parse_day(last)?.num_days_from_monday() as usize;
+            if first > last {
+                return Err(format!(
+                    "Day range {} must go from Monday towards Sunday",
+                    item
+                ));
+            }
+            Ok(WEEK[first..=last].to_vec())
+        }
+        None => Ok(vec![parse_day(item)?]),
+    }
+}
+
+/// Weekday from its English name or abbreviation, plural allowed as in "Tuesdays".
+fn parse_day(name: &str) -> Result<Weekday, String> {
+    name.parse::<Weekday>()
+        .or_else(|_e| name.trim_end_matches('s').parse::<Weekday>())
+        .map_err(|_e| format!("Unknown day {} in schedule", name))
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let days = if self.days == WEEK {
+            "daily".to_string()
+        } else if self.days == WEEK[..5] {
+            "weekdays".to_string()
+        } else if self.days == WEEK[5..] {
+            "weekends".to_string()
+        } else {
+            let names: Vec<String> = self.days.iter().map(|day| day.to_string()).collect();
+            names.join(",")
+        };
+        let time_format = if self.time.second() == 0 {
+            "%H:%M"
+        } else {
+            "%H:%M:%S"
+        };
+        write!(f, "{} at {}", days, self.time.format(time_format))
+    }
+}
+
+impl Serialize for Schedule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Schedule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let expression = String::deserialize(deserializer)?;
+        expression.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Clap validator for schedule expressions.
+pub fn validate_schedule_input(input: String) -> Result<(), String> {
+    input.parse::<Schedule>().map(|_schedule| ())
+}
+
+/// Clap validator for a number of upcoming slots to fill.
+pub fn validate_slot_count_input(input: String) -> Result<(), String> {
+    match input.parse::<usize>() {
+        Ok(count) if (1..=80).contains(&count) => Ok(()),
+        _ => Err(format!(
+            "Number of slots must be between 1 and 80, got {}",
+            input
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_schedule_expressions() {
+        let weekdays: Schedule = "Weekdays at 11:30".parse().unwrap();
+        assert_eq!(weekdays.time, NaiveTime::from_hms(11, 30, 0));
+        assert_eq!(weekdays.to_string(), "weekdays at 11:30");
+        assert!(weekdays.matches(NaiveDate::from_ymd(2022, 2, 18)));
+        assert!(!weekdays.matches(NaiveDate::from_ymd(2022, 2, 19)));
+
+        let twice: Schedule = "Tue, thursdays 16:00".parse().unwrap();
+        assert_eq!(twice.to_string(), "Tue,Thu at 16:00");
+        assert!(twice.matches(NaiveDate::from_ymd(2022, 2, 15)));
+        assert!(!twice.matches(NaiveDate::from_ymd(2022, 2, 16)));
+
+        let range: Schedule = "fri,mon-wed at 09:15:30".parse().unwrap();
+        assert_eq!(range.to_string(), "Mon,Tue,Wed,Fri at 09:15:30");
+        assert_eq!(
+            "every day 8:00".parse::<Schedule>().unwrap().to_string(),
+            "daily at 08:00"
+        );
+    }
+
+    #[test]
+    fn invalid_schedule_expressions() {
+        for input in [
+            "",
+            "11:30",
+            "weekdays",
+            "Tue,Thu at noon",
+            "Fri-Mon 10:00",
+            "Someday 10:00",
+        ] {
+            assert!(input.parse::<Schedule>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn schedule_saved_as_expression() {
+        let schedule: Schedule = serde_json::from_str("\"Tue,Thu 16:00\"").unwrap();
+        assert_eq!(
+            serde_json::to_string(&schedule).unwrap(),
+            "\"Tue,Thu at 16:00\""
+        );
+        assert!(serde_json::from_str::<Schedule>("\"Tue,Thu\"").is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use super::history::{HistoryEntry, HistoryStatus};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// Assignments of a member over the period.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct MemberStats {
+    pub member: String,
+    pub count: usize,
+    /// Target date of the last assignment in the period.
+    pub last_assigned: Option<NaiveDate>,
+    /// Difference from an even split: positive when assigned more than the others.
+    pub spread: f64,
+}
+
+/// Fairness report of the assignments over a period.
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<NaiveDate>,
+    /// Assignments of a member, counted once per member.
+    pub total: usize,
+    /// Assignments each member would have with an even split.
+    pub even_split: f64,
+    /// Largest spread from the even split, in either direction.
+    pub max_spread: f64,
+    /// Most assigned first.
+    pub members: Vec<MemberStats>,
+}
+
+impl Stats {
+    /// Counts the assignments still standing, scheduled or posted, with a target date in the period.
+    /// Every member of `members` is reported, including those never assigned.
+    /// Entries without a known target date use their posting date.
+    pub fn compute(
+        entries: &[HistoryEntry],
+        members: &[String],
+        role: Option<&str>,
+        since: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+    ) -> Stats {
+        let mut counts: Vec<MemberStats> = members
+            .iter()
+            .map(|member| MemberStats {
+                member: member.clone(),
+                count: 0,
+                last_assigned: None,
+                spread: 0.0,
+            })
+            .collect();
+        let standing = entries.iter().filter(|entry| {
+            matches!(
+                entry.status,
+                HistoryStatus::Scheduled | HistoryStatus::Posted
+            )
+        });
+        for entry in standing {
+            if role.is_some() && entry.role.as_deref() != role {
+                continue;
+            }
+            let date = entry
+                .target_date
+                .unwrap_or(entry.post_at)
+                .naive_local()
+                .date();
+            if since.is_some_and(|since| date < since) || until.is_some_and(|until| date > until) {
+                continue;
+            }
+            for member in &entry.members {
+                let index = match counts.iter().position(|stats| &stats.member == member) {
+                    Some(index) => index,
+                    // Former members still count in the period.
+                    None => {
+                        counts.push(MemberStats {
+                            member: member.clone(),
+                            count: 0,
+                            last_assigned: None,
+                            spread: 0.0,
+                        });
+                        counts.len() - 1
+                    }
+                };
+                let stats = &mut counts[index];
+                stats.count += 1;
+                stats.last_assigned = stats.last_assigned.max(Some(date));
+            }
+        }
+        let total: usize = counts.iter().map(|stats| stats.count).sum();
+        let even_split = if counts.is_empty() {
+            0.0
+        } else {
+            total as f64 / counts.len() as f64
+        };
+        for stats in counts.iter_mut() {
+            stats.spread = stats.count as f64 - even_split;
+        }
+        let max_spread = counts
+            .iter()
+            .map(|stats| stats.spread.abs())
+            .fold(0.0, f64::max);
+        counts.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then(a.last_assigned.cmp(&b.last_assigned))
+                .then(a.member.cmp(&b.member))
+        });
+        Stats {
+            role: role.map(String::from),
+            since,
+            until,
+            total,
+            even_split,
+            max_spread,
+            members: counts,
+        }
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} assignments for {} members, {:.2} each on an even split. Largest spread: {:.2}",
+            self.total,
+            self.members.len(),
+            self.even_split,
+            self.max_spread
+        )?;
+        writeln!(
+            f,
+            "{:<14} {:>5} {:>8}  Last assigned",
+            "Member", "Count", "Spread"
+        )?;
+        for stats in &self.members {
+            let last = match stats.last_assigned {
+                Some(date) => date.to_string(),
+                None => "never".to_string(),
+            };
+            writeln!(
+                f,
+                "{:<14} {:>5} {:>+8.2}  {}",
+                stats.member, stats.count, stats.spread, last
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bot::history::history_entry;
+    use chrono::{Local, TimeZone};
+
+    fn entry(members: &[&str], day: u32, status: HistoryStatus) -> HistoryEntry {
+        let target_date = Local.ymd(2022, 2, day).and_hms(11, 30, 0);
+        history_entry(&format!("Q{}", day), members, target_date, status)
+    }
+
+    #[test]
+    fn counts_standing_assignments_in_period() {
+        let entries = vec![
+            entry(&["a"], 1, HistoryStatus::Posted),
+            entry(&["a", "b"], 2, HistoryStatus::Posted),
+            entry(&["b"], 3, HistoryStatus::Cancelled),
+            entry(&["a"], 4, HistoryStatus::Scheduled),
+            entry(&["old"], 5, HistoryStatus::Scheduled),
+            entry(&["b"], 20, HistoryStatus::Scheduled),
+        ];
+        let members = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let stats = Stats::compute(
+            &entries,
+            &members,
+            Some("joke"),
+            None,
+            Some(NaiveDate::from_ymd(2022, 2, 10)),
+        );
+        assert_eq!(stats.total, 5);
+        let summary: Vec<(&str, usize, Option<NaiveDate>)> = stats
+            .members
+            .iter()
+            .map(|m| (m.member.as_str(), m.count, m.last_assigned))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a", 3, Some(NaiveDate::from_ymd(2022, 2, 4))),
+                ("b", 1, Some(NaiveDate::from_ymd(2022, 2, 2))),
+                ("old", 1, Some(NaiveDate::from_ymd(2022, 2, 5))),
+                ("c", 0, None),
+            ]
+        );
+        assert_eq!(stats.even_split, 1.25);
+        assert_eq!(stats.max_spread, 1.75);
+
+        let other_role = Stats::compute(&entries, &members, Some("standup"), None, None);
+        assert_eq!(other_role.total, 0);
+    }
+}
@@ -0,0 +1,132 @@
+use super::history::{HistoryEntry, HistoryStatus};
+use crate::api::ScheduledMessageObject;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fmt::Display;
+
+/// Difference found between the local history and the messages scheduled on Slack.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncKind {
+    /// No longer scheduled, as its time has passed.
+    Posted,
+    /// No longer scheduled, while its time hasn't come yet: someone deleted it.
+    DeletedElsewhere,
+    /// Scheduled by another operator, unknown to the local history.
+    Imported,
+}
+
+/// A change applied to the history by `sync`, with the resulting history entry.
+#[derive(Debug, Serialize)]
+pub struct SyncChange {
+    pub change: SyncKind,
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+}
+
+impl Display for SyncChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let what = match self.change {
+            SyncKind::Posted => "Posted",
+            SyncKind::DeletedElsewhere => "Deleted elsewhere",
+            SyncKind::Imported => "Imported",
+        };
+        write!(
+            f,
+            "{}: {} ({}), post time {}. Schedule ID: {}",
+            what,
+            self.entry.members.join(", "),
+            self.entry.role.as_deref().unwrap_or("unknown role"),
+            self.entry.post_at,
+            self.entry.id
+        )
+    }
+}
+
+/// Compares the history of the channel with what Slack has scheduled.
+/// `known` is the current state of each message of the channel, as from `History::current`.
+/// `scheduled` must be the complete list from Slack, or every missing message is taken for deleted.
+/// `imported` builds the entry of a scheduled message unknown to the history. Messages without metadata nor mention aren't assignments, and are ignored.
+pub fn reconcile(
+    known: &[HistoryEntry],
+    scheduled: &[ScheduledMessageObject],
+    now: DateTime<Local>,
+    imported: impl Fn(&ScheduledMessageObject) -> HistoryEntry,
+) -> Vec<SyncChange> {
+    let mut changes = Vec::new();
+    for entry in known {
+        if entry.status != HistoryStatus::Scheduled
+            || scheduled.iter().any(|message| message.id == entry.id)
+        {
+            continue;
+        }
+        let (change, status) = if entry.post_at <= now {
+            (SyncKind::Posted, HistoryStatus::Posted)
+        } else {
+            (SyncKind::DeletedElsewhere, HistoryStatus::Cancelled)
+        };
+        changes.push(SyncChange {
+            change,
+            entry: entry.with_status(status),
+        });
+    }
+    for message in scheduled {
+        if known.iter().any(|entry| entry.id == message.id) || message.assigned_members().is_empty()
+        {
+            continue;
+        }
+        changes.push(SyncChange {
+            change: SyncKind::Imported,
+            entry: imported(message),
+        });
+    }
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::api::scheduled_message;
+    use crate::bot::history::history_entry;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn reconcile_history_with_slack() {
+        let now = Local.ymd(2022, 2, 15).and_hms(12, 0, 0);
+        let past = now - Duration::hours(2);
+        let future = now + Duration::days(1);
+        let known = vec![
+            history_entry("kept", &["U1"], future, HistoryStatus::Scheduled),
+            history_entry("posted", &["U1"], past, HistoryStatus::Scheduled),
+            history_entry("deleted", &["U1"], future, HistoryStatus::Scheduled),
+            history_entry("cancelled", &["U1"], future, HistoryStatus::Cancelled),
+        ];
+        let messages = vec![
+            scheduled_message("kept", future, "<@U1> joke"),
+            scheduled_message("other", future, "<@U2> joke"),
+            scheduled_message("reminder", future, "No assignment here"),
+        ];
+        let changes = reconcile(&known, &messages, now, |message| HistoryEntry {
+            id: message.id.clone(),
+            members: message.mentioned_members(),
+            ..history_entry("", &[], message.post_at, HistoryStatus::Scheduled)
+        });
+        let summary: Vec<(&str, SyncKind, HistoryStatus)> = changes
+            .iter()
+            .map(|c| (c.entry.id.as_str(), c.change, c.entry.status))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("posted", SyncKind::Posted, HistoryStatus::Posted),
+                (
+                    "deleted",
+                    SyncKind::DeletedElsewhere,
+                    HistoryStatus::Cancelled
+                ),
+                ("other", SyncKind::Imported, HistoryStatus::Scheduled),
+            ]
+        );
+        assert_eq!(changes[2].entry.members, vec!["U2"]);
+    }
+}
@@ -0,0 +1,393 @@
+use crate::dates::Locale;
+use chrono::{DateTime, Datelike, Local};
+use std::fmt::Display;
+
+/// Used when the config doesn't define any template. The joke role keeps its original wording.
+/// Plural templates are for assignments of several members.
+pub fn default_template(locale: Locale, joke: bool, plural: bool) -> &'static str {
+    match (locale, joke, plural) {
+        (Locale::En, true, _) => "{reroll}{mention} will be in charge of a joke on {date}!",
+        (Locale::Fr, true, false) => "{reroll}{mention} sera en charge d'une blague le {date} !",
+        (Locale::Fr, true, true) => "{reroll}{mention} seront en charge d'une blague le {date} !",
+        (Locale::En, false, false) => "{reroll}{mention} will be the {role} on {date}!",
+        (Locale::En, false, true) => "{reroll}{mention} will share the {role} duty on {date}!",
+        (Locale::Fr, false, false) => "{reroll}{mention} sera {role} le {date} !",
+        (Locale::Fr, false, true) => "{reroll}{mention} seront {role} le {date} !",
+    }
+}
+
+/// What `{reroll}` renders to for rerolls. Renders to nothing otherwise.
+fn reroll_prefix(locale: Locale) -> &'static str {
+    match locale {
+        Locale::En => "Reroll: ",
+        Locale::Fr => "Nouveau tirage : ",
+    }
+}
+
+/// Values that can be inserted in a template, written as `{name}` in the template.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Placeholder {
+    /// `{mention}`: Slack mention of the members, notifying them.
+    Mention,
+    /// `{name}`: display name of the members, without notification.
+    Name,
+    /// `{date}`: target date, with localized weekday and month names.
+    Date,
+    /// `{weekday}`: localized weekday of the target date.
+    Weekday,
+    /// `{post_time}`: time at which the message is posted, as HH:MM.
+    PostTime,
+    /// `{reroll}`: localized "Reroll: " when the message is a reroll, empty otherwise.
+    Reroll,
+    /// `{role}`: name of the duty, such as "standup host".
+    Role,
+    /// `{cc}`: mention of the role's cc user group, empty without one.
+    Cc,
+}
+
+impl Placeholder {
+    const ALL: [Placeholder; 8] = [
+        Placeholder::Mention,
+        Placeholder::Name,
+        Placeholder::Date,
+        Placeholder::Weekday,
+        Placeholder::PostTime,
+        Placeholder::Reroll,
+        Placeholder::Role,
+        Placeholder::Cc,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            Placeholder::Mention => "mention",
+            Placeholder::Name => "name",
+            Placeholder::Date => "date",
+            Placeholder::Weekday => "weekday",
+            Placeholder::PostTime => "post_time",
+            Placeholder::Reroll => "reroll",
+            Placeholder::Role => "role",
+            Placeholder::Cc => "cc",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Placeholder> {
+        Self::ALL.iter().find(|p| p.key() == key).copied()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Value(Placeholder),
+}
+
+/// Everything a template may need to be rendered.
+pub struct MessageContext<'a> {
+    /// Members of the assignment, usually a single one.
+    pub members: &'a [String],
+    /// Only needed when the template uses `{name}`, in the same order as members. Falls back to the member IDs.
+    pub names: Option<Vec<String>>,
+    pub target_date: &'a DateTime<Local>,
+    pub post_at: &'a DateTime<Local>,
+    pub reroll: bool,
+    pub locale: Locale,
+    /// Label of the role.
+    pub role: &'a str,
+    /// Mention of the user group to cc, if any.
+    pub cc: Option<String>,
+}
+
+/// A validated message template. Literal braces are written `{{` and `}}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageTemplate {
+    tokens: Vec<Token>,
+}
+
+impl MessageTemplate {
+    /// Parses and validates the template, so a broken one is caught before anything is sent to Slack.
+    pub fn parse(input: &str) -> Result<MessageTemplate, String> {
+        let mut tokens = Vec::new();
+        let mut text = String::new();
+        let mut chars = input.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut key = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(k) => key.push(k),
+                            None => return Err(format!("Unclosed placeholder {{{}", key)),
+                        }
+                    }
+                    let placeholder = Placeholder::from_key(&key).ok_or_else(|| {
+                        format!(
+                            "Unknown placeholder {{{}}}. Available: {}",
+                            key,
+                            Placeholder::ALL
+                                .iter()
+                                .map(|p| format!("{{{}}}", p.key()))
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        )
+                    })?;
+                    if !text.is_empty() {
+                        tokens.push(Token::Text(std::mem::take(&mut text)));
+                    }
+                    tokens.push(Token::Value(placeholder));
+                }
+                '}' => return Err("Unmatched }. Write }} for a literal brace".to_string()),
+                _ => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            tokens.push(Token::Text(text));
+        }
+        let template = MessageTemplate { tokens };
+        if !template.uses(Placeholder::Mention) {
+            return Err(format!(
+                "Template \"{}\" must contain {{mention}}, or nobody gets notified",
+                input
+            ));
+        }
+        Ok(template)
+    }
+
+    pub fn uses(&self, placeholder: Placeholder) -> bool {
+        self.tokens.contains(&Token::Value(placeholder))
+    }
+
+    pub fn render(&self, context: &MessageContext) -> String {
+        let mut rendered = String::new();
+        for token in &self.tokens {
+            match token {
+                Token::Text(text) => rendered.push_str(text),
+                Token::Value(Placeholder::Mention) => {
+                    let mentions: Vec<String> = context
+                        .members
+                        .iter()
+                        .map(|member| format!("<@{}>", member))
+                        .collect();
+                    rendered.push_str(&context.locale.join(&mentions))
+                }
+                Token::Value(Placeholder::Name) => rendered.push_str(
+                    &context
+                        .locale
+                        .join(context.names.as_deref().unwrap_or(context.members)),
+                ),
+                Token::Value(Placeholder::Date) => rendered.push_str(
+                    &context
+                        .locale
+                        .format_date(context.target_date.naive_local().date()),
+                ),
+                Token::Value(Placeholder::Weekday) => {
+                    rendered.push_str(context.locale.weekday_name(context.target_date.weekday()))
+                }
+                Token::Value(Placeholder::PostTime) => {
+                    rendered.push_str(&context.post_at.format("%H:%M").to_string())
+                }
+                Token::Value(Placeholder::Reroll) => {
+                    if context.reroll {
+                        rendered.push_str(reroll_prefix(context.locale))
+                    }
+                }
+                Token::Value(Placeholder::Role) => rendered.push_str(context.role),
+                Token::Value(Placeholder::Cc) => {
+                    if let Some(cc) = &context.cc {
+                        rendered.push_str(cc)
+                    }
+                }
+            }
+        }
+        rendered
+    }
+}
+
+impl Display for MessageTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for token in &self.tokens {
+            match token {
+                Token::Text(text) => write!(f, "{}", text.replace('{', "{{").replace('}', "}}"))?,
+                Token::Value(placeholder) => write!(f, "{{{}}}", placeholder.key())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Clap validator for template arguments.
+pub fn validate_template_input(input: String) -> Result<(), String> {
+    MessageTemplate::parse(&input).map(|_template| ())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample_dates() -> (DateTime<Local>, DateTime<Local>) {
+        (
+            Local.ymd(2022, 2, 15).and_hms(11, 30, 0),
+            Local.ymd(2022, 2, 14).and_hms(9, 5, 0),
+        )
+    }
+
+    #[test]
+    fn default_template_renders() {
+        let template = MessageTemplate::parse(default_template(Locale::En, true, false)).unwrap();
+        let (target_date, post_at) = sample_dates();
+        let mut context = MessageContext {
+            members: &["U123".to_string()],
+            names: None,
+            target_date: &target_date,
+            post_at: &post_at,
+            reroll: false,
+            locale: Locale::En,
+            role: "joke",
+            cc: None,
+        };
+        assert_eq!(
+            template.render(&context),
+            "<@U123> will be in charge of a joke on Tuesday, February 15!"
+        );
+        context.reroll = true;
+        assert_eq!(
+            template.render(&context),
+            "Reroll: <@U123> will be in charge of a joke on Tuesday, February 15!"
+        );
+    }
+
+    #[test]
+    fn localized_default_template_renders() {
+        let template = MessageTemplate::parse(default_template(Locale::Fr, true, false)).unwrap();
+        let (target_date, post_at) = sample_dates();
+        let context = MessageContext {
+            members: &["U123".to_string()],
+            names: None,
+            target_date: &target_date,
+            post_at: &post_at,
+            reroll: true,
+            locale: Locale::Fr,
+            role: "joke",
+            cc: None,
+        };
+        assert_eq!(
+            template.render(&context),
+            "Nouveau tirage : <@U123> sera en charge d'une blague le mardi 15 février !"
+        );
+    }
+
+    #[test]
+    fn role_default_template_renders() {
+        let template = MessageTemplate::parse(default_template(Locale::En, false, false)).unwrap();
+        let (target_date, post_at) = sample_dates();
+        let context = MessageContext {
+            members: &["U123".to_string()],
+            names: None,
+            target_date: &target_date,
+            post_at: &post_at,
+            reroll: false,
+            locale: Locale::En,
+            role: "standup host",
+            cc: None,
+        };
+        assert_eq!(
+            template.render(&context),
+            "<@U123> will be the standup host on Tuesday, February 15!"
+        );
+    }
+
+    #[test]
+    fn pair_renders_all_members() {
+        let template = MessageTemplate::parse(default_template(Locale::Fr, true, true)).unwrap();
+        let (target_date, post_at) = sample_dates();
+        let context = MessageContext {
+            members: &["U1".to_string(), "U2".to_string()],
+            names: None,
+            target_date: &target_date,
+            post_at: &post_at,
+            reroll: false,
+            locale: Locale::Fr,
+            role: "joke",
+            cc: None,
+        };
+        assert_eq!(
+            template.render(&context),
+            "<@U1> et <@U2> seront en charge d'une blague le mardi 15 février !"
+        );
+    }
+
+    #[test]
+    fn all_placeholders_render() {
+        let template =
+            MessageTemplate::parse("{{{name}}} {mention} on {weekday}, posted {post_time}")
+                .unwrap();
+        let (target_date, post_at) = sample_dates();
+        let context = MessageContext {
+            members: &["U123".to_string()],
+            names: Some(vec!["spengler".to_string()]),
+            target_date: &target_date,
+            post_at: &post_at,
+            reroll: false,
+            locale: Locale::En,
+            role: "joke",
+            cc: None,
+        };
+        assert_eq!(
+            template.render(&context),
+            "{spengler} <@U123> on Tuesday, posted 09:05"
+        );
+        assert!(template.uses(Placeholder::Name));
+        assert!(!template.uses(Placeholder::Reroll));
+    }
+
+    #[test]
+    fn cc_renders_when_set() {
+        let template = MessageTemplate::parse("{mention} tells the joke. {cc}").unwrap();
+        let (target_date, post_at) = sample_dates();
+        let mut context = MessageContext {
+            members: &["U123".to_string()],
+            names: None,
+            target_date: &target_date,
+            post_at: &post_at,
+            reroll: false,
+            locale: Locale::En,
+            role: "joke",
+            cc: Some("<!subteam^S0614TZR7>".to_string()),
+        };
+        assert_eq!(
+            template.render(&context),
+            "<@U123> tells the joke. <!subteam^S0614TZR7>"
+        );
+        context.cc = None;
+        assert_eq!(template.render(&context), "<@U123> tells the joke. ");
+    }
+
+    #[test]
+    fn invalid_templates_are_rejected() {
+        for input in [
+            "{mention} on {dat}",
+            "{mention} on {date",
+            "{mention} }",
+            "Nobody gets notified on {date}",
+            "",
+        ] {
+            assert!(MessageTemplate::parse(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let input = "{{literal}} {mention} {reroll}";
+        let template = MessageTemplate::parse(input).unwrap();
+        assert_eq!(template.to_string(), input);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::api::SlackApiClient;
+use crate::bot::{BotConfig, SlackBot};
+use crate::dates::next_working_days;
+use crate::output::OutputFormat;
+use async_std::task;
+use chrono::{Local, NaiveDate};
+use log::{debug, error, info, warn};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How often the daemon wakes up to check for shutdown and config changes.
+const TICK: Duration = Duration::from_secs(1);
+/// Delay before filling again, when Slack couldn't be reached or failed to schedule a slot.
+const RETRY_DELAY: Duration = Duration::from_secs(10 * 60);
+
+/// Settings of the `daemon` command.
+pub struct DaemonOptions {
+    /// How many working days ahead are kept assigned.
+    pub working_days: usize,
+    /// Roles to keep assigned. All the roles of the config when empty, including roles added while running.
+    pub roles: Vec<String>,
+}
+
+/// Keeps the next working days assigned, filling them once a day and whenever the config file changes, until SIGTERM or SIGINT.
+/// Rotation state is saved after each fill, as with `rota`.
+pub async fn run<Api: SlackApiClient>(
+    mut bot: SlackBot<Api>,
+    options: DaemonOptions,
+    output: OutputFormat,
+) {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [SIGTERM, SIGINT] {
+        if let Err(err) = signal_hook::flag::register(signal, Arc::clone(&shutdown)) {
+            error!("Couldn't listen to signal {}: {}", signal, err);
+            return;
+        }
+    }
+    let config_path = BotConfig::get_config_path();
+    let mut config_modified = modified_time(&config_path);
+    let mut last_fill: Option<NaiveDate> = None;
+    let mut retry_at: Option<Instant> = None;
+    info!(
+        "Daemon started, keeping the next {} working days assigned",
+        options.working_days
+    );
+
+    while !shutdown.load(Ordering::Relaxed) {
+        let modified = modified_time(&config_path);
+        if modified != config_modified {
+            config_modified = modified;
+            match BotConfig::reload(&config_path) {
+                Ok(config) => {
+                    info!("Config file changed, reloaded it");
+                    bot.set_config(config);
+                    // New members or roles may change what's missing.
+                    last_fill = None;
+                    retry_at = None;
+                }
+                Err(_) => error!("Keeping the previous config until the file is fixed"),
+            }
+        }
+
+        let today = Local::today().naive_local();
+        let waiting_retry = retry_at.is_some_and(|retry_at| Instant::now() < retry_at);
+        if last_fill != Some(today) && !waiting_retry {
+            let complete = fill(&mut bot, &options, today, output).await;
+            bot.save();
+            // Saving isn't a change to reload.
+            config_modified = modified_time(&config_path);
+            if complete {
+                last_fill = Some(today);
+                retry_at = None;
+            } else {
+                error!(
+                    "Daily fill incomplete, trying again in {} minutes",
+                    RETRY_DELAY.as_secs() / 60
+                );
+                retry_at = Some(Instant::now() + RETRY_DELAY);
+            }
+        }
+        task::sleep(TICK).await;
+    }
+    info!("Received shutdown signal, stopping daemon");
+}
+
+/// Fills every role, returning whether all of them could be checked and every missing slot scheduled.
+async fn fill<Api: SlackApiClient>(
+    bot: &mut SlackBot<Api>,
+    options: &DaemonOptions,
+    today: NaiveDate,
+    output: OutputFormat,
+) -> bool {
+    // Roles with a schedule fill their own days within the same period.
+    let until = match next_working_days(today, options.working_days).last() {
+        Some(until) => *until,
+        None => return true,
+    };
+    let roles: Vec<String> = if options.roles.is_empty() {
+        bot.settings()
+            .role_names()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    } else {
+        options.roles.clone()
+    };
+    debug!("Filling roles {:?} until {}", roles, until);
+    let mut scheduled = Vec::new();
+    let mut complete = true;
+    for role in roles {
+        let days = bot.target_days(&role, today, until);
+        match bot.fill(&role, &days).await {
+            Ok(filled) => {
+                if filled.failed > 0 {
+                    warn!("Failed to schedule {} slots of {}", filled.failed, role);
+                    complete = false;
+                }
+                scheduled.extend(filled.scheduled);
+            }
+            Err(_) => complete = false,
+        }
+    }
+    info!("Daily fill done, {} assignments scheduled", scheduled.len());
+    if !scheduled.is_empty() {
+        output.print_list(&scheduled);
+    }
+    complete
+}
+
+/// Modification time of the config file, `None` while it doesn't exist.
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    path.metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -1,5 +1,6 @@
-use chrono::{DateTime, Datelike, Local, Weekday};
+use chrono::{DateTime, Datelike, Local, NaiveDate, Weekday};
 use log::debug;
+use serde::{Deserialize, Serialize};
 
 const WEEKDAYS: [Weekday; 5] = [
     Weekday::Mon,
@@ -28,6 +29,111 @@ impl IsWeekday for chrono::NaiveDate {
     }
 }
 
+/// Language of the announcements, for the default templates and the date names.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+}
+
+impl std::str::FromStr for Locale {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_str() {
+            "en" => Ok(Locale::En),
+            "fr" => Ok(Locale::Fr),
+            _ => Err(format!("Unsupported locale {}. Available: en, fr", input)),
+        }
+    }
+}
+
+impl Locale {
+    pub fn weekday_name(&self, weekday: Weekday) -> &'static str {
+        let names = match self {
+            Locale::En => [
+                "Monday",
+                "Tuesday",
+                "Wednesday",
+                "Thursday",
+                "Friday",
+                "Saturday",
+                "Sunday",
+            ],
+            Locale::Fr => [
+                "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+            ],
+        };
+        names[weekday.num_days_from_monday() as usize]
+    }
+
+    /// Name of the month, from 1 for January to 12 for December, as given by chrono.
+    pub fn month_name(&self, month: u32) -> &'static str {
+        let names = match self {
+            Locale::En => [
+                "January",
+                "February",
+                "March",
+                "April",
+                "May",
+                "June",
+                "July",
+                "August",
+                "September",
+                "October",
+                "November",
+                "December",
+            ],
+            Locale::Fr => [
+                "janvier",
+                "février",
+                "mars",
+                "avril",
+                "mai",
+                "juin",
+                "juillet",
+                "août",
+                "septembre",
+                "octobre",
+                "novembre",
+                "décembre",
+            ],
+        };
+        names[month as usize - 1]
+    }
+
+    /// Formats the date the way it reads in a sentence, such as "Tuesday, February 15" or "mardi 15 février".
+    /// French writes the first of the month as an ordinal, "lundi 1er août".
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        let weekday = self.weekday_name(date.weekday());
+        let month = self.month_name(date.month());
+        match self {
+            Locale::En => format!("{}, {} {}", weekday, month, date.day()),
+            Locale::Fr if date.day() == 1 => format!("{} 1er {}", weekday, month),
+            Locale::Fr => format!("{} {} {}", weekday, date.day(), month),
+        }
+    }
+
+    /// Joins items the way a list reads in a sentence, such as "a, b and c".
+    pub fn join(&self, items: &[String]) -> String {
+        let and = match self {
+            Locale::En => "and",
+            Locale::Fr => "et",
+        };
+        match items.split_last() {
+            None => String::new(),
+            Some((last, [])) => last.clone(),
+            Some((last, others)) => format!("{} {} {}", others.join(", "), and, last),
+        }
+    }
+}
+
+pub fn validate_locale_input(input_locale: String) -> Result<(), String> {
+    input_locale.parse::<Locale>().map(|_locale| ())
+}
+
 pub fn validate_time_input(input_time: String) -> Result<(), String> {
     match chrono::NaiveTime::parse_from_str(&input_time, "%H:%M:%S") {
         Ok(_v) => Ok(()),
@@ -68,6 +174,38 @@ pub fn validate_date_input(input_date: String) -> Result<(), String> {
     Ok(())
 }
 
+/// Validates a date bounding a period, which may be in the past, unlike `validate_date_input`.
+pub fn validate_period_date_input(input_date: String) -> Result<(), String> {
+    parse_period_date(&input_date).map(|_date| ())
+}
+
+pub fn parse_period_date(input_date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(input_date, "%Y-%m-%d")
+        .map_err(|_e| format!("Not a date: {}. Example format: 2022-02-15", input_date))
+}
+
+/// The next `count` weekdays after the given day.
+pub fn next_working_days(after: NaiveDate, count: usize) -> Vec<NaiveDate> {
+    after
+        .iter_days()
+        .skip(1)
+        .filter(|day| day.is_weekday())
+        .take(count)
+        .collect()
+}
+
+/// Clap validator for a number of working days to keep assigned ahead.
+/// Slack only schedules messages up to 120 days ahead, which 80 working days stay within.
+pub fn validate_working_days_input(input: String) -> Result<(), String> {
+    match input.parse::<usize>() {
+        Ok(days) if (1..=80).contains(&days) => Ok(()),
+        _ => Err(format!(
+            "Number of working days must be between 1 and 80, got {}",
+            input
+        )),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -88,6 +226,49 @@ mod test {
         assert!(!naive_sunday.is_weekday());
     }
 
+    #[test]
+    fn next_working_days_skip_weekends() {
+        let friday = NaiveDate::from_ymd(2022, 2, 18);
+        assert_eq!(
+            next_working_days(friday, 3),
+            vec![
+                NaiveDate::from_ymd(2022, 2, 21),
+                NaiveDate::from_ymd(2022, 2, 22),
+                NaiveDate::from_ymd(2022, 2, 23),
+            ]
+        );
+        assert!(validate_working_days_input("0".to_string()).is_err());
+        assert!(validate_working_days_input("81".to_string()).is_err());
+        assert!(validate_working_days_input("10".to_string()).is_ok());
+    }
+
+    #[test]
+    fn localized_date_format() {
+        let date = NaiveDate::from_ymd(2022, 2, 15);
+        assert_eq!(Locale::En.format_date(date), "Tuesday, February 15");
+        assert_eq!(Locale::Fr.format_date(date), "mardi 15 février");
+        let date = NaiveDate::from_ymd(2022, 8, 1);
+        assert_eq!(Locale::Fr.format_date(date), "lundi 1er août");
+        assert_eq!(Locale::En.format_date(date), "Monday, August 1");
+        let date = NaiveDate::from_ymd(2022, 8, 2);
+        assert_eq!(Locale::Fr.format_date(date), "mardi 2 août");
+    }
+
+    #[test]
+    fn localized_list_join() {
+        let items = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        assert_eq!(Locale::En.join(&items[..1]), "a");
+        assert_eq!(Locale::En.join(&items[..2]), "a and b");
+        assert_eq!(Locale::Fr.join(&items), "a, b et c");
+    }
+
+    #[test]
+    fn locale_parsing() {
+        assert_eq!("fr".parse::<Locale>(), Ok(Locale::Fr));
+        assert_eq!("EN".parse::<Locale>(), Ok(Locale::En));
+        assert!(validate_locale_input("jp".to_string()).is_err());
+    }
+
     #[test]
     fn input_time_validation_fail() {
         let test_input = ["", "noo", "16:00:62", "10:00"];
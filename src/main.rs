@@ -1,57 +1,120 @@
 mod api;
 mod bot;
+mod daemon;
 mod dates;
+mod output;
 use async_std::task;
-use bot::{BotConfig, SlackBot};
+use bot::{
+    read_member_csv, validate_export_format_input, validate_schedule_input,
+    validate_slot_count_input, validate_template_input, BotConfig, ExportFormat, History,
+    ImportFilter, Prompt, ScheduleOptions, SlackBot, JOKE_ROLE,
+};
 
-use clap::{App, Arg, SubCommand};
-use dates::{validate_date_input, validate_time_input};
-use log::{debug, info, warn};
+use clap::{App, Arg, ArgGroup, ArgMatches, SubCommand};
+use daemon::DaemonOptions;
+use dates::{
+    parse_period_date, validate_date_input, validate_locale_input, validate_period_date_input,
+    validate_time_input, validate_working_days_input,
+};
+use log::{debug, error, info, warn};
+use output::{to_json, validate_output_input, OutputFormat};
 use simplelog::{Config, LevelFilter, SimpleLogger};
+use std::path::Path;
 
-use crate::api::ProdSlackApiClient;
+use crate::api::{DryRunSlackApiClient, ProdSlackApiClient, SlackApiClient};
 
 const API_KEY_ENV_NAME: &str = "SLACK_API_KEY";
 const BOT_NAME: &str = "Slack-R";
 const CLI_VERSION: &str = "0.1.4";
 const CONFIG_FILE_PATH_ENV_VAR: &str = "SLACK_R_CONFIG_FILE_PATH";
 const DEFAULT_CONFIG_PATH: &str = "./config.json";
+const HISTORY_FILE_PATH_ENV_VAR: &str = "SLACK_R_HISTORY_FILE_PATH";
+const DEFAULT_HISTORY_PATH: &str = "./history.jsonl";
 
 /// Entry point and define command line interface.
 fn main() {
-    let joke_command = SubCommand::with_name("joke")
-        .about("Notifies who has to find a joke.")
+    let joke_command =
+        schedule_args(SubCommand::with_name("joke").about("Notifies who has to find a joke."));
+    let rota_command = schedule_args(
+        SubCommand::with_name("rota")
+            .about("Notifies who is in charge of a role, such as the standup host. Works like `joke` for other rotating duties.")
+            .arg(Arg::with_name("role")
+                .required(true)
+                .takes_value(true)
+                .help("Name of the role, as added with `add role`.")
+            ),
+    );
+    let reroll_command = SubCommand::with_name("reroll")
+        .about("Reroll for the next day, or the given day")
+        .long_about("Reroll for the next day, or the given day, allowing you to preview the randomly selected name to filter out.
+If the assignment of that day is still scheduled, it is replaced at its original time without the previous member.
+Otherwise, a reroll message is posted shortly.")
         .arg(Arg::with_name("day")
             .short("d")
             .long("day")
             .takes_value(true)
-            .multiple(true)
             .validator(validate_date_input)
-            .help("Select a specific day to include in the message. Format as YYYY-MM-DD. Only dates in the future are allowed. Defaults to tomorrow. Can accept several dates in a single run"))
-        .arg(Arg::with_name("post_on")
-            .short("p")
-            .long("post_on")
+            .help("Day of the assignment to reroll. Format as YYYY-MM-DD. Defaults to tomorrow.")
+        )
+        .arg(Arg::with_name("exclude")
+            .short("x")
+            .long("exclude")
             .takes_value(true)
-            .multiple(false)
-            .validator(validate_date_input)
-            .help("Select a specific day to schedule the message. 
-Format as YYYY-MM-DD. Only dates in the future but before the --day argument allowed. 
-Default to be calculated before the target day, before weekends. 
-This arg allows overriding of the auto-calculated.
-Currently unspecified behavior with several --day. Use only one when specificying the post date.")
-        );
-    let reroll_command = SubCommand::with_name("reroll")
-        .about("Reroll for the next day")
-        .help("Reroll for the next day, allowing you to preview the randomly selected name to filter out.");
+            .multiple(true)
+            .help("Members never to draw, as Slack ID, email or name. Allows rerolling without confirmation prompts.")
+        )
+        .arg(seed_arg())
+        .arg(role_arg());
+
+    let swap_command = SubCommand::with_name("swap")
+        .about("Exchanges the members assigned to two scheduled dates")
+        .long_about(
+            "Exchanges the members assigned to two scheduled dates, when two members trade days.
+Both messages are scheduled again at their original post time, and the original ones are deleted.",
+        )
+        .arg(
+            Arg::with_name("date_a")
+                .required(true)
+                .takes_value(true)
+                .validator(validate_date_input)
+                .help("First date to swap. Format as YYYY-MM-DD."),
+        )
+        .arg(
+            Arg::with_name("date_b")
+                .required(true)
+                .takes_value(true)
+                .validator(validate_date_input)
+                .help("Second date to swap. Format as YYYY-MM-DD."),
+        )
+        .arg(role_arg());
 
     let add_member_command = SubCommand::with_name("member")
-        .about("Adds a member ID to config, taking email as input to lookup Slack ID.")
-        .arg(Arg::with_name("email")
+        .about("Adds a member ID to config, looking up the user from their Slack ID, email, @handle or name.")
+        .arg(Arg::with_name("member")
             .required(true)
             .takes_value(true)
-            .validator(validate_email)
-            .help("Input the Slack's registered email of the user to add in config. The bot must have `users:read.email` permission")
-        );
+            .help("Slack ID, email, @handle, display name or real name of the user to add. Emails need the `users:read.email` permission, names `users:read`. When several users have the name, asks which one.")
+        )
+        .arg(role_arg());
+
+    let add_members_command = SubCommand::with_name("members")
+        .about("Adds members in bulk, from a CSV file of emails or IDs, or from a user group.")
+        .long_about("Adds members in bulk, from a CSV file of emails or IDs, or from a user group.
+IDs are found in one listing of the workspace users and emails are looked up concurrently, then a summary of those found, not found and already present is shown before adding them.")
+        .arg(Arg::with_name("from_csv")
+            .long("from-csv")
+            .takes_value(true)
+            .value_name("file")
+            .help("CSV file with emails or Slack IDs in any column. Other cells, such as headers and names, are ignored. Emails need the `users:read.email` permission."))
+        .arg(Arg::with_name("usergroup")
+            .long("usergroup")
+            .takes_value(true)
+            .value_name("@handle")
+            .help("User group whose members to add, as @handle or ID. Needs the `usergroups:read` permission."))
+        .group(ArgGroup::with_name("source")
+            .args(&["from_csv", "usergroup"])
+            .required(true))
+        .arg(role_arg());
 
     // let add_token_command = SubCommand::with_name("token")
     //     .about("Adds a Slack API token to the config.")
@@ -61,15 +124,17 @@ Currently unspecified behavior with several --day. Use only one when specificyin
     //         .help("Saves the input token to config, so it doesn't need to eb set as environnment variable. A new token must be acquired form Slack, and will represent the bot's authentication and permissions")
     //     );
 
-    let add_channel_command = SubCommand::with_name("channel")
+    let add_channel_command = import_filter_args(SubCommand::with_name("channel")
         .about("Sets the channel to where the bot will post, and adds all the channels's users in config. Only one channel is allowed per configuration file, so any previously set channel will be overwitten. The bot will join the channel if not already in.")
+        .long_about("Sets the channel to where the bot will post, and adds all the channels's users in config. Only one channel is allowed per configuration file, so any previously set channel will be overwitten. The bot will join the channel if not already in.
+Bots, app users, deactivated and guest accounts aren't added, unless included with the flags below. Each account left out is reported.")
         .arg(Arg::with_name("channel")
             .required(true)
             // .short("c")
             // .long("channel")
             .takes_value(true)
             .help("Specifies the channel to add")
-        );
+        ));
 
     let add_times_command = SubCommand::with_name("time")
         .about("Sets the target time, post_at time, day offset. By default, both target time and post_at times are set to local 11:30 (AM).")
@@ -89,14 +154,212 @@ Currently unspecified behavior with several --day. Use only one when specificyin
             .long("day_offset")
             .takes_value(true)
             .help("Sets how many days in advance to schedule relative to the target time.")
+        )
+        .arg(role_arg());
+
+    let add_template_command = SubCommand::with_name("template")
+        .about("Adds variants of the announcement message, one of which is picked at random for each message.")
+        .long_about("Adds variants of the announcement message, one of which is picked at random for each message.
+Placeholders are written between braces, and `{mention}` is required:
+    {mention}   Slack mention of the selected member
+    {name}      display name of the selected member, without notification
+    {date}      target date, with localized weekday and month names
+    {weekday}   localized weekday of the target date
+    {post_time} time at which the message is posted
+    {reroll}    \"Reroll: \" for rerolls, empty otherwise
+    {cc}        mention of the user group set with `add cc`, empty otherwise
+Write {{ and }} for literal braces.")
+        .arg(Arg::with_name("template")
+            .required(true)
+            .takes_value(true)
+            .multiple(true)
+            .validator(validate_template_input)
+            .help("Template of the message, such as \"{mention} will be in charge of a joke on {weekday}!\"")
+        )
+        .arg(Arg::with_name("replace")
+            .long("replace")
+            .help("Replaces all the existing templates instead of adding variants.")
+        )
+        .arg(role_arg());
+
+    let add_role_command = SubCommand::with_name("role")
+        .about("Adds a rotating duty, such as standup host, with its own members, times, templates and rotation. Starts with the same members as the joke.")
+        .arg(Arg::with_name("role")
+            .required(true)
+            .takes_value(true)
+            .help("Name of the role, used in commands such as `rota <role>`.")
+        )
+        .arg(Arg::with_name("label")
+            .long("label")
+            .takes_value(true)
+            .help("Name of the duty in the default announcement, such as \"standup host\". Defaults to the role name.")
+        );
+
+    let add_schedule_command = SubCommand::with_name("schedule")
+        .about("Sets which days and at what time the role needs an assignment, instead of every weekday at the target time.")
+        .long_about("Sets which days and at what time the role needs an assignment, instead of every weekday at the target time.
+Days are a comma separated list of weekday names, ranges such as Mon-Wed, or one of weekdays, weekends and daily, followed by a 24h time.
+Examples: \"weekdays at 11:30\", \"Tue,Thu 16:00\", \"Mon-Wed,Fri at 09:15\". `joke`, `rota` and `daemon` fill the next slots when no day is given.")
+        .arg(Arg::with_name("schedule")
+            .required(true)
+            .takes_value(true)
+            .validator(|input| if input.eq_ignore_ascii_case("none") { Ok(()) } else { validate_schedule_input(input) })
+            .help("Schedule expression, or \"none\" to remove it.")
+        )
+        .arg(role_arg());
+
+    let add_cc_command = SubCommand::with_name("cc")
+        .about("Mentions a user group in the announcements of the role, such as @jokes-committee.")
+        .long_about(
+            "Mentions a user group in the announcements of the role, such as @jokes-committee.
+It goes where templates have {cc}, or after the message. Needs the `usergroups:read` permission.",
+        )
+        .arg(
+            Arg::with_name("usergroup")
+                .required(true)
+                .takes_value(true)
+                .help("User group, as @handle or ID, or \"none\" to stop mentioning it."),
+        )
+        .arg(role_arg());
+
+    let add_usergroup_command = SubCommand::with_name("usergroup")
+        .about("Keeps the members of the role in sync with a user group.")
+        .long_about("Keeps the members of the role in sync with a user group.
+Before each draw, members who joined the group are added, and those who left it are removed from the role and its rotation.
+Bots, app users, guests and deactivated accounts are left out. Needs the `usergroups:read` permission.")
+        .arg(Arg::with_name("usergroup")
+            .required(true)
+            .takes_value(true)
+            .help("User group, as @handle or ID, or \"none\" to stop syncing. Members stay as they are.")
+        )
+        .arg(role_arg());
+
+    let add_locale_command = SubCommand::with_name("locale")
+        .about("Sets the language of the default announcement templates, and of the dates in announcements.")
+        .arg(Arg::with_name("locale")
+            .required(true)
+            .takes_value(true)
+            .validator(validate_locale_input)
+            .help("Locale code. Available: en, fr")
         );
 
     let add_command = SubCommand::with_name("add")
         .about("Adds various data to config, possibly fetching data from Slack")
         .subcommand(add_member_command)
+        .subcommand(add_members_command)
         // .subcommand(add_token_command)
         .subcommand(add_channel_command)
-        .subcommand(add_times_command);
+        .subcommand(add_times_command)
+        .subcommand(add_template_command)
+        .subcommand(add_locale_command)
+        .subcommand(add_schedule_command)
+        .subcommand(add_cc_command)
+        .subcommand(add_usergroup_command)
+        .subcommand(add_role_command);
+
+    let members_sync_command = import_filter_args(SubCommand::with_name("sync")
+        .about("Updates the members from those of the channel, showing the difference before applying it.")
+        .long_about("Updates the members from those of the channel, showing the difference before applying it.
+Channel members not in the role are added to it. Members who left the channel are removed from every role, and from their rotation.
+Bots, app users, deactivated and guest accounts aren't added, unless included with the flags below. Each account left out is reported.")
+        .arg(role_arg()));
+
+    let members_list_command = SubCommand::with_name("list")
+        .about("Lists the members of the role with their display name, and whether they are disabled or had their turn.")
+        .arg(role_arg());
+
+    let members_remove_command = SubCommand::with_name("remove")
+        .about("Removes members from the role and its rotation. Their past assignments stay in the history.")
+        .arg(member_input_arg())
+        .arg(role_arg());
+
+    let members_disable_command = SubCommand::with_name("disable")
+        .about("Takes members out of the draw of the role, such as during holidays, until enabled again.")
+        .long_about("Takes members out of the draw of the role, such as during holidays, until enabled again.
+They stay members, keep their place in the rotation and their history. The rotation ends without them.")
+        .arg(member_input_arg())
+        .arg(role_arg());
+
+    let members_enable_command = SubCommand::with_name("enable")
+        .about("Puts disabled members back in the draw of the role.")
+        .arg(member_input_arg())
+        .arg(role_arg());
+
+    let members_command = SubCommand::with_name("members")
+        .about("Manages the members of the roles.")
+        .subcommand(members_list_command)
+        .subcommand(members_remove_command)
+        .subcommand(members_disable_command)
+        .subcommand(members_enable_command)
+        .subcommand(members_sync_command);
+
+    let history_command = SubCommand::with_name("history")
+        .about("Prints the assignments recorded in the local history, with their current status.");
+
+    let stats_command = SubCommand::with_name("stats")
+        .about("Reports how many times each member was assigned, and how far it is from an even split.")
+        .long_about("Reports how many times each member was assigned, when they were last assigned, and how far it is from an even split.
+Counts the assignments of the local history which weren't cancelled or rerolled, and the messages still scheduled on Slack.")
+        .arg(Arg::with_name("since")
+            .long("since")
+            .takes_value(true)
+            .validator(validate_period_date_input)
+            .help("Only counts assignments from this target date on. Format as YYYY-MM-DD.")
+        )
+        .arg(Arg::with_name("until")
+            .long("until")
+            .takes_value(true)
+            .validator(validate_period_date_input)
+            .help("Only counts assignments up to this target date. Format as YYYY-MM-DD.")
+        )
+        .arg(Arg::with_name("role")
+            .long("role")
+            .takes_value(true)
+            .help("Only counts the assignments of this role. Defaults to all roles.")
+        );
+
+    let export_command = SubCommand::with_name("export")
+        .about("Exports past and upcoming assignments as CSV or as an iCalendar file.")
+        .long_about("Exports the assignments of the local history and the messages still scheduled on Slack.
+CSV has a line per assignment, with its status. ICS has an all-day event per assignment still standing, naming the members, to subscribe to in calendar apps.")
+        .arg(Arg::with_name("format")
+            .long("format")
+            .takes_value(true)
+            .required(true)
+            .validator(validate_export_format_input)
+            .help("One of csv, ics.")
+        )
+        .arg(Arg::with_name("file")
+            .long("file")
+            .short("f")
+            .takes_value(true)
+            .help("File to write to, overwritten if it exists. Prints to stdout when not set.")
+        );
+
+    let sync_command = SubCommand::with_name("sync")
+        .about("Reconciles the local history and rotation with the messages scheduled on Slack.")
+        .long_about("Reconciles the local history and rotation with the messages scheduled on Slack, when several people run the bot on the same channel.
+Assignments no longer scheduled are marked as posted when their time has passed, or as cancelled when deleted elsewhere, giving their members their turn back.
+Assignments scheduled by other operators are added to the history and the rotation.");
+
+    let daemon_command = SubCommand::with_name("daemon")
+        .about("Keeps running, and makes sure the next working days are assigned, every day.")
+        .long_about("Keeps running, and makes sure the next working days are assigned, every day, for every role.
+Days already assigned are left alone. The config file is reloaded when it changes, which fills again for new members and roles.
+Stops on SIGTERM or SIGINT. Run with -vv to log each action.")
+        .arg(Arg::with_name("days")
+            .long("days")
+            .takes_value(true)
+            .default_value("10")
+            .validator(validate_working_days_input)
+            .help("How many working days ahead to keep assigned, up to 80.")
+        )
+        .arg(Arg::with_name("role")
+            .long("role")
+            .takes_value(true)
+            .multiple(true)
+            .help("Only keeps these roles assigned. Defaults to all roles.")
+        );
 
     let scheduled_command =
         SubCommand::with_name("scheduled").about("Prints all scheduled messages for the bot.");
@@ -156,12 +419,46 @@ Currently unspecified behavior with several --day. Use only one when specificyin
                 .multiple(true)
                 .help("Sets the level of verbosity, the more \"v\" the more verbose, up to -vvv."),
         )
+        .arg(
+            Arg::with_name("yes")
+                .short("y")
+                .long("yes")
+                .help("Answers yes to every confirmation."),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .default_value("text")
+                .validator(validate_output_input)
+                .help("Prints results as human readable text, or as JSON for other tools. Fields are documented in the readme. One of text, json."),
+        )
+        .arg(
+            Arg::with_name("dry_run")
+                .long("dry-run")
+                .help("Reads from Slack, but only prints the messages that would be scheduled, deleted, or the channels joined. Nothing is saved either."),
+        )
+        .arg(
+            Arg::with_name("no_input")
+                .long("no-input")
+                .conflicts_with("yes")
+                .help("Never waits for input: rerolls take the first draw, and other confirmations are declined. Implied when input is not a terminal."),
+        )
         .subcommand(joke_command)
+        .subcommand(rota_command)
         .subcommand(reroll_command)
+        .subcommand(swap_command)
         .subcommand(config_command)
         .subcommand(add_command)
         .subcommand(cancel_command)
-        .subcommand(scheduled_command);
+        .subcommand(scheduled_command)
+        .subcommand(history_command)
+        .subcommand(stats_command)
+        .subcommand(export_command)
+        .subcommand(sync_command)
+        .subcommand(daemon_command)
+        .subcommand(members_command);
     // CLI defined,
     let matches = app.get_matches();
 
@@ -198,62 +495,241 @@ Currently unspecified behavior with several --day. Use only one when specificyin
                     API_KEY_ENV_NAME)
     };
     let api = ProdSlackApiClient::new(token);
-    let mut bot = SlackBot::new(config, api);
+    let prompt = Prompt::from_flags(matches.is_present("yes"), matches.is_present("no_input"));
+    if matches.is_present("dry_run") {
+        warn!("Dry run: nothing will be sent to Slack or saved.");
+        let mut bot = SlackBot::new(config, DryRunSlackApiClient::new(api));
+        bot.set_prompt(prompt);
+        bot.set_history(History::from_env());
+        dispatch(bot, &matches);
+    } else {
+        let mut bot = SlackBot::new(config, api);
+        bot.set_prompt(prompt);
+        bot.set_history(History::from_env());
+        dispatch(bot, &matches);
+    }
+    info!("Finished execution");
+}
+
+/// Runs the subcommand, with either the real or the dry run Slack client.
+fn dispatch<Api: SlackApiClient>(mut bot: SlackBot<Api>, matches: &ArgMatches) {
     info!("Bot initialized");
+    let output: OutputFormat = matches.value_of("output").unwrap().parse().unwrap();
 
     debug!("Dispatching");
     match matches.subcommand() {
         ("joke", Some(args)) => {
             debug!("Joke subcommand");
-            let input_date_args = args.values_of("day").unwrap_or_default().collect();
-            let scheduled_day_arg = args.value_of("post_on");
-            let scheduled = task::block_on(bot.joke(input_date_args, scheduled_day_arg));
-            for joke in scheduled {
-                println!("{}", joke);
-            }
+            let scheduled = task::block_on(bot.joke(parse_schedule_options(args)));
+            output.print_list(&scheduled);
+            bot.save();
+        }
+        ("rota", Some(args)) => {
+            debug!("Rota subcommand");
+            let role = args.value_of("role").unwrap();
+            let scheduled = task::block_on(bot.rota(role, parse_schedule_options(args)));
+            output.print_list(&scheduled);
+            bot.save();
         }
-        ("reroll", Some(_args)) => {
+        ("reroll", Some(args)) => {
             debug!("Reroll subcommand");
-            task::block_on(bot.reroll());
+            let seed = parse_seed(args);
+            let role = args.value_of("role").unwrap();
+            let day = args.value_of("day");
+            let exclude = args
+                .values_of("exclude")
+                .map(|values| values.collect())
+                .unwrap_or_default();
+            let rerolled = task::block_on(bot.reroll(role, day, seed, exclude));
+            output.print_list(&rerolled.into_iter().collect::<Vec<_>>());
+            bot.save();
+        }
+        ("swap", Some(args)) => {
+            debug!("Swap subcommand");
+            let role = args.value_of("role").unwrap();
+            let date_a = args.value_of("date_a").unwrap();
+            let date_b = args.value_of("date_b").unwrap();
+            let swapped = task::block_on(bot.swap(role, date_a, date_b));
+            output.print_list(&swapped);
         }
         ("scheduled", _) => {
             debug!("Scheduled subcommand");
-            task::block_on(bot.check_scheduled_messages());
+            let messages = task::block_on(bot.scheduled_messages());
+            output.print_list(&messages);
+        }
+        ("stats", Some(args)) => {
+            debug!("Stats subcommand");
+            let since = args
+                .value_of("since")
+                .map(|d| parse_period_date(d).unwrap());
+            let until = args
+                .value_of("until")
+                .map(|d| parse_period_date(d).unwrap());
+            let role = args.value_of("role");
+            if let Some(stats) = task::block_on(bot.stats(role, since, until)) {
+                output.print(&stats);
+            }
+        }
+        ("export", Some(args)) => {
+            debug!("Export subcommand");
+            let format: ExportFormat = args.value_of("format").unwrap().parse().unwrap();
+            let export = task::block_on(bot.export(format));
+            match args.value_of("file") {
+                Some(path) => match std::fs::write(path, export) {
+                    Ok(_) => info!("Exported to {}", path),
+                    Err(err) => error!("Couldn't write export to {}. Error: {}", path, err),
+                },
+                None => print!("{}", export),
+            }
+        }
+        ("sync", _) => {
+            debug!("Sync subcommand");
+            let changes = task::block_on(bot.sync());
+            output.print_list(&changes);
+            bot.save();
+        }
+        ("daemon", Some(args)) => {
+            debug!("Daemon subcommand");
+            let options = DaemonOptions {
+                working_days: args.value_of("days").unwrap().parse().unwrap(),
+                roles: args
+                    .values_of("role")
+                    .unwrap_or_default()
+                    .map(String::from)
+                    .collect(),
+            };
+            task::block_on(daemon::run(bot, options, output));
+        }
+        ("history", _) => {
+            debug!("History subcommand");
+            output.print_list(&bot.history());
         }
         ("cancel", Some(args)) => {
             debug!("Cancel subcommand");
             let id_values = args.values_of("id").unwrap().collect();
-            task::block_on(bot.cancel_scheduled_message(id_values));
+            let cancellations = task::block_on(bot.cancel_scheduled_message(id_values));
+            output.print_list(&cancellations);
+        }
+        ("members", Some(args)) => {
+            match args.subcommand() {
+                ("sync", Some(sync_args)) => {
+                    debug!("Members sync subcommand");
+                    let role = sync_args.value_of("role").unwrap();
+                    let filter = parse_import_filter(sync_args);
+                    if let Some(diff) = task::block_on(bot.sync_members(role, filter)) {
+                        output.print(&diff);
+                    }
+                }
+                ("list", Some(list_args)) => {
+                    debug!("Members list subcommand");
+                    let role = list_args.value_of("role").unwrap();
+                    output.print_list(&task::block_on(bot.list_members(role)));
+                }
+                ("remove", Some(remove_args)) => {
+                    debug!("Members remove subcommand");
+                    let role = remove_args.value_of("role").unwrap();
+                    let inputs = remove_args.values_of("member").unwrap().collect();
+                    output.print_list(&task::block_on(bot.remove_members(role, inputs)));
+                }
+                ("disable", Some(disable_args)) => {
+                    debug!("Members disable subcommand");
+                    let role = disable_args.value_of("role").unwrap();
+                    let inputs = disable_args.values_of("member").unwrap().collect();
+                    let updates = task::block_on(bot.set_members_disabled(role, inputs, true));
+                    output.print_list(&updates);
+                }
+                ("enable", Some(enable_args)) => {
+                    debug!("Members enable subcommand");
+                    let role = enable_args.value_of("role").unwrap();
+                    let inputs = enable_args.values_of("member").unwrap().collect();
+                    let updates = task::block_on(bot.set_members_disabled(role, inputs, false));
+                    output.print_list(&updates);
+                }
+                _ => panic!(
+                    "Can only list, remove, disable, enable or sync members! See `slack-r help members`"
+                ),
+            }
+            bot.save();
         }
         ("add", Some(args)) => {
             match args.subcommand() {
                 ("member", Some(member_args)) => {
                     debug!("Add Member subcommand");
-                    let email = member_args.value_of("email").unwrap();
-                    task::block_on(bot.add_member_from_email(email));
+                    let role = member_args.value_of("role").unwrap();
+                    let input = member_args.value_of("member").unwrap();
+                    task::block_on(bot.add_member(role, input));
+                }
+                ("members", Some(members_args)) => {
+                    debug!("Add Members subcommand");
+                    let role = members_args.value_of("role").unwrap();
+                    let inputs = match members_args.value_of("from_csv") {
+                        Some(path) => read_member_csv(Path::new(path)),
+                        None => {
+                            let usergroup = members_args.value_of("usergroup").unwrap();
+                            task::block_on(bot.usergroup_users(usergroup)).unwrap_or_default()
+                        }
+                    };
+                    if let Some(summary) = task::block_on(bot.import_members(role, inputs)) {
+                        output.print(&summary);
+                    }
                 }
                 ("channel", Some(channel_args)) => {
                     debug!("Add Channel subcommand");
                     let channel = channel_args.value_of("channel").unwrap();
-                    task::block_on(bot.add_channel(channel));
+                    let filter = parse_import_filter(channel_args);
+                    let excluded = task::block_on(bot.add_channel(channel, filter));
+                    output.print_list(&excluded);
                 }
                 ("time", Some(times_args)) => {
                     debug!("Add times subcommand");
+                    let role = times_args.value_of("role").unwrap();
                     let target_time_opt = times_args.value_of("target");
                     if let Some(target_time) = target_time_opt {
-                        bot.add_target_time(target_time);
+                        bot.add_target_time(role, target_time);
                     }
                     let post_at_opt = times_args.value_of("post_at");
                     if let Some(offset) = post_at_opt {
-                        bot.add_post_time(offset);
+                        bot.add_post_time(role, offset);
                     }
                     let day_offset_opt = times_args.value_of("day_offset");
                     if let Some(offset) = day_offset_opt {
-                        bot.set_post_day_offset(offset);
+                        bot.set_post_day_offset(role, offset);
                     }
                 }
+                ("template", Some(template_args)) => {
+                    debug!("Add template subcommand");
+                    let role = template_args.value_of("role").unwrap();
+                    let templates = template_args.values_of("template").unwrap().collect();
+                    bot.add_templates(role, templates, template_args.is_present("replace"));
+                }
+                ("role", Some(role_args)) => {
+                    debug!("Add role subcommand");
+                    let role = role_args.value_of("role").unwrap();
+                    bot.add_role(role, role_args.value_of("label"));
+                }
+                ("schedule", Some(schedule_args)) => {
+                    debug!("Add schedule subcommand");
+                    let role = schedule_args.value_of("role").unwrap();
+                    bot.set_schedule(role, schedule_args.value_of("schedule").unwrap());
+                }
+                ("cc", Some(cc_args)) => {
+                    debug!("Add cc subcommand");
+                    let role = cc_args.value_of("role").unwrap();
+                    task::block_on(bot.set_cc(role, cc_args.value_of("usergroup").unwrap()));
+                }
+                ("usergroup", Some(usergroup_args)) => {
+                    debug!("Add usergroup subcommand");
+                    let role = usergroup_args.value_of("role").unwrap();
+                    let usergroup = usergroup_args.value_of("usergroup").unwrap();
+                    let excluded = task::block_on(bot.set_member_usergroup(role, usergroup));
+                    output.print_list(&excluded);
+                }
+                ("locale", Some(locale_args)) => {
+                    debug!("Add locale subcommand");
+                    bot.set_locale(locale_args.value_of("locale").unwrap());
+                }
                 _ => panic!(
-                    "Can only add channel, token or individual members! See `slack-r help add`"
+                    "Can only add channel, times, schedules, templates, locale, roles, members, cc or user groups! See `slack-r help add`"
                 ),
             }
             bot.save();
@@ -266,12 +742,23 @@ Currently unspecified behavior with several --day. Use only one when specificyin
             let channel = args.value_of("channel");
             let token = args.value_of("token");
             let target_time = args.value_of("target_time");
-            task::block_on(bot.config(members, channel, token, target_time));
+            let (summary, excluded) =
+                task::block_on(bot.config(members, channel, token, target_time));
+            if let Some(summary) = summary {
+                output.print(&summary);
+            }
+            if channel.is_some() {
+                output.print_list(&excluded);
+            }
+            match output {
+                OutputFormat::Text => println!("{:?}", bot.settings().redacted()),
+                OutputFormat::Json => println!("{}", to_json(&bot.settings().redacted())),
+            }
+            bot.save_with_confirmation();
         }
 
         _ => panic!("No subcommand matching! See `slack-r help` for available commands."),
     };
-    info!("Finished execution");
 }
 
 #[derive(Debug)]
@@ -281,6 +768,142 @@ pub enum SlackRError {
     NoMemberToSelect,
     CorruptedConfig,
     WriteConfig,
+    WriteHistory,
+    InvalidTemplate,
+}
+
+/// Shared arguments of the commands scheduling announcements.
+fn schedule_args<'a, 'b>(command: App<'a, 'b>) -> App<'a, 'b> {
+    command
+        .arg(Arg::with_name("day")
+            .short("d")
+            .long("day")
+            .takes_value(true)
+            .multiple(true)
+            .validator(validate_date_input)
+            .help("Select a specific day to include in the message. Format as YYYY-MM-DD. Only dates in the future are allowed. Defaults to the next slot of the role's schedule, or tomorrow without one. Can accept several dates in a single run"))
+        .arg(Arg::with_name("next")
+            .short("n")
+            .long("next")
+            .takes_value(true)
+            .conflicts_with("day")
+            .validator(validate_slot_count_input)
+            .help("Makes sure the next N slots of the role's schedule are assigned, skipping those already scheduled. Slots are weekdays for roles without schedule."))
+        .arg(Arg::with_name("post_on")
+            .short("p")
+            .long("post_on")
+            .takes_value(true)
+            .multiple(false)
+            .validator(validate_date_input)
+            .help("Select a specific day to schedule the message. 
+Format as YYYY-MM-DD. Only dates in the future but before the --day argument allowed. 
+Default to be calculated before the target day, before weekends. 
+This arg allows overriding of the auto-calculated.
+Currently unspecified behavior with several --day. Use only one when specificying the post date.")
+        )
+        .arg(seed_arg())
+        .arg(Arg::with_name("pair")
+            .long("pair")
+            .help("Assigns two distinct members together for each date, mentioned in the same message. Overrides the `members_per_assignment` of the role.")
+        )
+        .arg(Arg::with_name("member")
+            .short("m")
+            .long("member")
+            .takes_value(true)
+            .multiple(true)
+            .conflicts_with("pair")
+            .help("Assigns this member instead of drawing one, such as a volunteer. Takes a Slack ID, an email, or a display name. Must be a member of the role. Repeat it for roles assigning several members per date, giving as many as the role assigns.")
+        )
+}
+
+/// Shared flags of the commands importing channel members, to include accounts left out by default.
+fn import_filter_args<'a, 'b>(command: App<'a, 'b>) -> App<'a, 'b> {
+    command
+        .arg(
+            Arg::with_name("include_bots")
+                .long("include-bots")
+                .help("Also imports bots and integrations."),
+        )
+        .arg(
+            Arg::with_name("include_apps")
+                .long("include-apps")
+                .help("Also imports app users."),
+        )
+        .arg(
+            Arg::with_name("include_deleted")
+                .long("include-deleted")
+                .help("Also imports deactivated accounts."),
+        )
+        .arg(
+            Arg::with_name("include_guests")
+                .long("include-guests")
+                .help("Also imports restricted accounts, such as guests."),
+        )
+}
+
+fn parse_import_filter(args: &ArgMatches) -> ImportFilter {
+    ImportFilter {
+        include_deleted: args.is_present("include_deleted"),
+        include_bots: args.is_present("include_bots"),
+        include_app_users: args.is_present("include_apps"),
+        include_guests: args.is_present("include_guests"),
+    }
+}
+
+/// Members of a role to act on, for the `members` subcommands.
+fn member_input_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("member")
+        .required(true)
+        .takes_value(true)
+        .multiple(true)
+        .help("Members of the role, as Slack ID, email or @handle.")
+}
+
+/// Shared `--role` argument, for commands working on a single role.
+fn role_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("role")
+        .long("role")
+        .takes_value(true)
+        .default_value(JOKE_ROLE)
+        .help("Name of the role, as added with `add role`.")
+}
+
+/// Shared `--seed` argument, for commands drawing a member.
+fn seed_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("seed")
+        .long("seed")
+        .takes_value(true)
+        .validator(validate_seed)
+        .help("Seed for the random draw, as an unsigned integer. Defaults to a seed derived from the target date and the channel, so anyone can re-run the draw and get the same result with the same members.")
+}
+
+fn parse_schedule_options<'a>(args: &'a clap::ArgMatches) -> ScheduleOptions<'a> {
+    ScheduleOptions {
+        days: args.values_of("day").unwrap_or_default().collect(),
+        post_on: args.value_of("post_on"),
+        seed: parse_seed(args),
+        members_per_assignment: if args.is_present("pair") {
+            Some(2)
+        } else {
+            None
+        },
+        members: args.values_of("member").unwrap_or_default().collect(),
+        next: args
+            .value_of("next")
+            .map(|next| next.parse().expect("Slot count was validated")),
+    }
+}
+
+fn parse_seed(args: &clap::ArgMatches) -> Option<u64> {
+    args.value_of("seed")
+        .map(|seed| seed.parse::<u64>().expect("Seed was validated"))
+}
+
+fn validate_seed(input_seed: String) -> Result<(), String> {
+    input_seed
+        .parse::<u64>()
+        .map(|_seed| ())
+        .map_err(|_e| format!("Seed {} must be an unsigned integer", input_seed))
 }
 
 fn validate_email(input_email: String) -> Result<(), String> {
@@ -0,0 +1,71 @@
+use serde::Serialize;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// How command results are printed on stdout. Logs and prompts always go to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    /// Human readable lines.
+    #[default]
+    Text,
+    /// A single JSON document, for other tools to consume.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "Unknown output format {}. Available: text, json",
+                input
+            )),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Prints results one per line, or as a JSON array, empty included.
+    pub fn print_list<T: Serialize + Display>(&self, items: &[T]) {
+        match self {
+            OutputFormat::Text => {
+                for item in items {
+                    println!("{}", item);
+                }
+            }
+            OutputFormat::Json => println!("{}", to_json(&items)),
+        }
+    }
+
+    /// Prints a single result, such as a report.
+    pub fn print<T: Serialize + Display>(&self, item: &T) {
+        match self {
+            OutputFormat::Text => print!("{}", item),
+            OutputFormat::Json => println!("{}", to_json(item)),
+        }
+    }
+}
+
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string_pretty(value).expect("Results are serializable")
+}
+
+/// Clap validator for the output format.
+pub fn validate_output_input(input: String) -> Result<(), String> {
+    input.parse::<OutputFormat>().map(|_format| ())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn output_format_parsing() {
+        assert_eq!("JSON".parse::<OutputFormat>(), Ok(OutputFormat::Json));
+        assert_eq!("text".parse::<OutputFormat>(), Ok(OutputFormat::Text));
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+}